@@ -1,757 +1,4706 @@
-use crate::core::line_history::{ChangeType, LineEntry, LineHistory, LineHistoryProvider};
-use crate::core::types::SortOrder;
+use crate::core::line_history::{
+    ChangeType, DiffHunk, DiffLine, DiffLineKind, FileHistory, FileLine, HistoryOptions, LineEntry,
+    LineHistory, LineHistoryProvider, LineSelector, Person, RangeBound, Trailer,
+};
+use crate::core::types::{CommitOrder, SortOrder};
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset, Local, Utc};
 use git2::Repository;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ffi::OsStr;
+use std::ops::RangeInclusive;
+use std::path::{Component, Path, PathBuf};
+
+mod cache;
+mod date;
+
+pub use cache::{Cache, CacheStats};
+use cache::{Comparison, LineMapping, LineMappings};
+
+/// How many commits past a re-created line its earlier deletion is looked for
+/// in, unless deleted lines are followed.
+const DELETED_LINE_SEARCH_DEPTH: u32 = 100;
 
 pub struct GitAdapter {
     repository: Repository,
+    /// Directory relative file paths are resolved against
+    base_dir: PathBuf,
+    follow_deleted: bool,
+    find_copies: bool,
+    ignore_whitespace: bool,
+    ignore_space_change: bool,
+    ignore_blank_lines: bool,
+    first_parent: bool,
+    use_mailmap: bool,
+    /// Lines of context around the hunks attached to entries, if any are
+    patch_context: Option<u32>,
+    cache: Cache,
+    use_cache: bool,
 }
 
 impl GitAdapter {
-    pub fn new(repo_path: &Path) -> Result<Self> {
-        let repository = Repository::open(repo_path)?;
-        Ok(Self { repository })
+    /// Opens the repository containing `path`, the way git itself finds it.
+    ///
+    /// `GIT_DIR`, `GIT_WORK_TREE` and `GIT_CEILING_DIRECTORIES` are honored, and
+    /// relative file paths are later resolved against `path`.
+    pub fn new(path: &Path) -> Result<Self> {
+        // libgit2 only reads GIT_DIR when it is not given a path to start from
+        let repository = if std::env::var_os("GIT_DIR").is_some() {
+            Repository::open_from_env()?
+        } else {
+            Repository::open_ext(
+                path,
+                git2::RepositoryOpenFlags::FROM_ENV,
+                std::iter::empty::<&OsStr>(),
+            )?
+        };
+        let base_dir = path.canonicalize()?;
+        // Linked worktrees share the objects, and so the cache
+        let cache = Cache::new(&common_dir(&repository));
+
+        Ok(Self {
+            repository,
+            base_dir,
+            follow_deleted: false,
+            find_copies: false,
+            ignore_whitespace: false,
+            ignore_space_change: false,
+            ignore_blank_lines: false,
+            first_parent: false,
+            use_mailmap: true,
+            patch_context: None,
+            cache,
+            use_cache: true,
+        })
+    }
+
+    /// Keeps tracking a line past a commit that deleted it, so that the history
+    /// of an earlier incarnation of a re-created line is included as well.
+    pub fn with_follow_deleted(mut self, follow_deleted: bool) -> Self {
+        self.follow_deleted = follow_deleted;
+        self
+    }
+
+    /// Looks for lines that arrive in a file from other files changed by the same
+    /// commit, and keeps tracking them in the file they came from.
+    pub fn with_find_copies(mut self, find_copies: bool) -> Self {
+        self.find_copies = find_copies;
+        self
+    }
+
+    /// Ignores whitespace when comparing lines, so that commits which only
+    /// reindent the tracked line are skipped.
+    pub fn with_ignore_whitespace(mut self, ignore_whitespace: bool) -> Self {
+        self.ignore_whitespace = ignore_whitespace;
+        self
+    }
+
+    /// Ignores changes in the amount of whitespace when comparing lines.
+    pub fn with_ignore_space_change(mut self, ignore_space_change: bool) -> Self {
+        self.ignore_space_change = ignore_space_change;
+        self
+    }
+
+    /// Ignores blank lines added or removed around the tracked line, so that
+    /// they do not make it look rewritten.
+    pub fn with_ignore_blank_lines(mut self, ignore_blank_lines: bool) -> Self {
+        self.ignore_blank_lines = ignore_blank_lines;
+        self
+    }
+
+    /// Follows only the first parent of merge commits, like
+    /// `git log --first-parent`; changes brought in by a merge are attributed to
+    /// the merge itself.
+    pub fn with_first_parent(mut self, first_parent: bool) -> Self {
+        self.first_parent = first_parent;
+        self
     }
 
+    /// Maps author and committer names and emails through the repository's
+    /// mailmap (`.mailmap`, `mailmap.file` and `mailmap.blob`); enabled by
+    /// default.
+    pub fn with_mailmap(mut self, use_mailmap: bool) -> Self {
+        self.use_mailmap = use_mailmap;
+        self
+    }
+
+    /// Attaches the hunks of the diff that changed the line to each entry, with
+    /// the given number of context lines, like `git log -p -U<n>`.
+    pub fn with_patch(mut self, context_lines: Option<u32>) -> Self {
+        self.patch_context = context_lines;
+        self
+    }
+
+    /// Keeps diff results in `.git/ombl/` to speed up later runs; enabled by
+    /// default.
+    pub fn with_cache(mut self, use_cache: bool) -> Self {
+        self.use_cache = use_cache;
+        self
+    }
+
+    /// The on-disk cache of the repository, whether it is used or not.
+    pub fn cache(&self) -> &Cache {
+        &self.cache
+    }
+
+    /// Turns a file path relative to the base directory, or an absolute one,
+    /// into a path relative to the root of the working tree.
+    fn resolve_path(&self, file_path: &str) -> Result<String> {
+        let Some(workdir) = self.repository.workdir() else {
+            // Without a working tree, paths can only be relative to the root
+            return Ok(path_to_slash(&normalize_path(Path::new(file_path))));
+        };
+        let workdir = workdir
+            .canonicalize()
+            .unwrap_or_else(|_| workdir.to_path_buf());
+
+        let path = normalize_path(&self.base_dir.join(file_path));
+        // Resolve symlinked directories, but not the file itself
+        let path = match (path.parent(), path.file_name()) {
+            (Some(parent), Some(file_name)) => parent
+                .canonicalize()
+                .map(|parent| parent.join(file_name))
+                .unwrap_or(path),
+            _ => path,
+        };
+
+        let relative = path.strip_prefix(&workdir).map_err(|_| {
+            anyhow::anyhow!(
+                "Path {} is outside the repository at {}",
+                file_path,
+                workdir.display()
+            )
+        })?;
+        Ok(path_to_slash(relative))
+    }
+
+    /// Collects the history of the lines picked by `selector`, returning the
+    /// first and last line of the range along with it.
     fn extract_full_line_history(
         &self,
         file_path: &str,
-        line_number: u32,
-        sort_order: SortOrder,
-        ignore_revs: &[String],
-        since: Option<&str>,
-        until: Option<&str>,
-    ) -> Result<Vec<LineEntry>> {
-        let commits =
-            self.find_commits_affecting_file(file_path, line_number, ignore_revs, since, until)?;
+        selector: &LineSelector,
+        options: &HistoryOptions,
+    ) -> Result<(u32, u32, Vec<LineEntry>)> {
+        let starting_file = self.starting_file(file_path, options)?;
+        let newest_lines = starting_file.newest_lines();
+        let newest_content = newest_lines.map_or(&[][..], |lines| lines.content);
+        let (start, end) = selector.resolve(&String::from_utf8_lossy(newest_content))?;
+        let line_count = newest_lines.map_or(0, Lines::count);
+        if start == 0 || end > line_count {
+            return Err(if start == end {
+                anyhow::anyhow!(
+                    "Line {} is out of range for {} ({} lines)",
+                    start,
+                    file_path,
+                    line_count
+                )
+            } else {
+                anyhow::anyhow!(
+                    "Lines {}-{} are out of range for {} ({} lines)",
+                    start,
+                    end,
+                    file_path,
+                    line_count
+                )
+            });
+        }
 
-        // Check if the file exists in the repository at all
-        if commits.is_empty() {
-            // Try to find the file in the current HEAD to see if it exists
-            let head = self.repository.head()?;
-            let head_commit = head.peel_to_commit()?;
-            let tree = head_commit.tree()?;
-            if tree.get_path(Path::new(file_path)).is_err() {
-                return Err(anyhow::anyhow!(
-                    "File not found in repository: {}",
-                    file_path
-                ));
+        let changes = self.collect_line_changes(file_path, &starting_file, start..=end, options)?;
+
+        // A commit that changed several lines of the range gets a single entry
+        let mut entries: Vec<SourcedEntry> = Vec::new();
+        let mut positions: HashMap<ChangeSource, usize> = HashMap::new();
+        for change in changes {
+            match positions.get(&change.source) {
+                Some(&position) => merge_entry(&mut entries[position].entry, change.entry),
+                None => {
+                    positions.insert(change.source, entries.len());
+                    entries.push(change);
+                }
             }
         }
 
-        let entries = self.convert_commits_to_entries(commits)?;
-        self.sort_entries_chronologically(entries, sort_order)
+        sort_newest_first(&mut entries, options.order);
+        // The walk leaves room for uncommitted changes, which may be more
+        // than the limit themselves
+        if let Some(limit) = options.limit {
+            entries.truncate(limit);
+        }
+        Ok((start, end, in_sort_order(entries, &options.sort_order)))
     }
 
-    fn find_commits_affecting_file(
+    /// Collects the history of every line of a file in a single walk.
+    fn extract_file_history(
         &self,
         file_path: &str,
-        line_number: u32,
-        ignore_revs: &[String],
-        since: Option<&str>,
-        until: Option<&str>,
-    ) -> Result<Vec<git2::Commit<'_>>> {
-        let mut commits = Vec::new();
-        let mut revwalk = self.repository.revwalk()?;
-        revwalk.push_head()?;
-        revwalk.set_sorting(git2::Sort::TIME)?;
-
-        let mut seen_commits = std::collections::HashSet::new();
+        options: &HistoryOptions,
+    ) -> Result<FileHistory> {
+        let starting_file = self.starting_file(file_path, options)?;
+        let Some(newest_lines) = starting_file.newest_lines() else {
+            return Ok(FileHistory::new(file_path.to_string()));
+        };
+        let line_count = newest_lines.count();
+
+        let mut history = FileHistory::new(file_path.to_string());
+        if line_count == 0 {
+            return Ok(history);
+        }
 
-        for commit_oid in revwalk {
-            let commit_oid = commit_oid?;
+        // The limit applies to each line, so the walk cannot stop early
+        let walk_options = HistoryOptions {
+            limit: None,
+            ..options.clone()
+        };
+        let changes =
+            self.collect_line_changes(file_path, &starting_file, 1..=line_count, &walk_options)?;
+
+        let mut entries_by_line: Vec<Vec<SourcedEntry>> =
+            (0..line_count).map(|_| Vec::new()).collect();
+        for change in changes {
+            entries_by_line[change.start_line as usize - 1].push(change);
+        }
 
-            if seen_commits.contains(&commit_oid) {
-                continue;
+        for (line_number, mut entries) in (1..=line_count).zip(entries_by_line) {
+            sort_newest_first(&mut entries, options.order);
+            if let Some(limit) = options.limit {
+                entries.truncate(limit);
             }
-            seen_commits.insert(commit_oid);
+            // Entries are newest first until they are put in the requested order
+            let latest = match options.sort_order {
+                _ if entries.is_empty() => None,
+                SortOrder::Desc => Some(0),
+                SortOrder::Asc => Some(entries.len() - 1),
+            };
+            history.add_line(FileLine {
+                line_number,
+                content: newest_lines.text(line_number).unwrap_or_default(),
+                entries: in_sort_order(entries, &options.sort_order),
+                latest,
+            });
+        }
 
-            let commit = self.repository.find_commit(commit_oid)?;
+        Ok(history)
+    }
 
-            // Check if this commit should be ignored
-            if self.should_ignore_commit(&commit, ignore_revs) {
-                continue;
-            }
+    /// Finds the file history starts from: its version in the starting
+    /// revision, along with its uncommitted versions when starting from HEAD.
+    fn starting_file(&self, file_path: &str, options: &HistoryOptions) -> Result<StartingFile<'_>> {
+        let (commit, hidden) = self.resolve_revision(options.rev.as_deref())?;
+        let blob = self
+            .find_blob(&commit.tree()?, file_path)?
+            .map(IndexedBlob::new);
+
+        // Local changes only sit on top of HEAD
+        let local_versions = match options.rev {
+            Some(_) => Vec::new(),
+            None => self.local_versions(file_path)?,
+        };
+        let tracked_by_index = local_versions
+            .iter()
+            .any(|version| version.kind == LocalChange::Staged);
+        if blob.is_none() && !tracked_by_index {
+            return Err(match &options.rev {
+                Some(rev) => anyhow::anyhow!("File not found in revision {}: {}", rev, file_path),
+                None => anyhow::anyhow!("File not found in repository: {}", file_path),
+            });
+        }
+        // Line numbers refer to the file on disk, so there must be one
+        let on_disk = local_versions
+            .iter()
+            .any(|version| version.kind == LocalChange::WorkingTree);
+        if tracked_by_index && !on_disk {
+            return Err(anyhow::anyhow!(
+                "File deleted in the working tree: {}; use --rev HEAD for its committed history",
+                file_path
+            ));
+        }
 
-            // Check if this commit should be filtered by date
-            if !self.should_filter_by_date(&commit, since, until)? {
-                continue;
+        Ok(StartingFile {
+            commit,
+            hidden,
+            blob,
+            local_versions,
+        })
+    }
+
+    /// Finds the uncommitted and committed changes to each of `lines`, which
+    /// refer to the newest version of the file.
+    fn collect_line_changes(
+        &self,
+        file_path: &str,
+        starting_file: &StartingFile,
+        lines: RangeInclusive<u32>,
+        options: &HistoryOptions,
+    ) -> Result<Vec<SourcedEntry>> {
+        let dates = self.date_range(options)?;
+        let mut changes = Vec::new();
+        let mut committed_lines = Vec::new();
+        let local_histories = self.follow_local_changes(
+            file_path,
+            lines,
+            &starting_file.local_versions,
+            starting_file.blob.as_ref(),
+            &dates,
+        )?;
+        for local_history in local_histories {
+            for (kind, entry) in local_history.entries {
+                changes.push(SourcedEntry {
+                    start_line: local_history.start_line,
+                    source: ChangeSource::Local(kind),
+                    key: SortKey {
+                        // The working tree sits on top of the index
+                        rank: match kind {
+                            LocalChange::WorkingTree => 0,
+                            LocalChange::Staged => 1,
+                        },
+                        author_time: entry.timestamp,
+                        committer_time: entry.timestamp,
+                    },
+                    entry,
+                });
+            }
+            if let Some(committed_line) = local_history.committed_line {
+                committed_lines.push((local_history.start_line, committed_line));
             }
+        }
 
-            if self.commit_affects_file(&commit, file_path)?
-                && self.commit_changes_line(file_path, line_number, &commit)?
-            {
-                commits.push(commit);
+        if committed_lines.is_empty() {
+            return Ok(changes);
+        }
+
+        let local_kinds: HashSet<ChangeSource> =
+            changes.iter().map(|change| change.source).collect();
+        let ignore_revs = self.collect_ignore_revs(options)?;
+        // Ordered by date, the newest changes may be anywhere in the ancestry,
+        // so the whole of it is walked and the limit applied once sorted
+        let limit = options
+            .limit
+            .filter(|_| options.order == CommitOrder::Topo)
+            .map(|limit| limit.saturating_sub(local_kinds.len()));
+        let commit_changes = self.find_commits_changing_lines(
+            starting_file,
+            file_path,
+            &committed_lines,
+            &ignore_revs,
+            &CommitFilter {
+                dates,
+                no_merges: options.no_merges,
+            },
+            limit,
+        )?;
+        let mailmap = if self.use_mailmap {
+            Some(self.repository.mailmap()?)
+        } else {
+            None
+        };
+
+        // Changes come grouped by commit in the order of the walk
+        let mut rank = 1;
+        let mut previous_commit = None;
+        for change in commit_changes {
+            let commit_id = change.commit.id();
+            if previous_commit != Some(commit_id) {
+                previous_commit = Some(commit_id);
+                rank += 1;
             }
+            let key = SortKey {
+                rank,
+                author_time: signature_time(&change.commit.author()),
+                committer_time: signature_time(&change.commit.committer()),
+            };
+            changes.push(SourcedEntry {
+                start_line: change.start_line,
+                source: ChangeSource::Commit(commit_id),
+                key,
+                entry: self.create_line_entry_from_commit(change, mailmap.as_ref())?,
+            });
+        }
+
+        Ok(changes)
+    }
+
+    /// Reads the uncommitted versions of a file, newest first: the one in the
+    /// working tree and the one in the index.
+    fn local_versions(&self, file_path: &str) -> Result<Vec<LocalVersion>> {
+        let Some(workdir) = self.repository.workdir() else {
+            return Ok(Vec::new());
+        };
+
+        let mut versions = Vec::new();
+        match std::fs::read(workdir.join(file_path)) {
+            Ok(content) => versions.push(LocalVersion::new(
+                LocalChange::WorkingTree,
+                content,
+                Local::now().fixed_offset(),
+            )),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        let mut index = self.repository.index()?;
+        index.read(false)?;
+        if let Some(entry) = index.get_path(Path::new(file_path), 0) {
+            let blob = self.repository.find_blob(entry.id)?;
+            versions.push(LocalVersion::new(
+                LocalChange::Staged,
+                blob.content().to_vec(),
+                DateTime::from_timestamp(i64::from(entry.mtime.seconds()), 0)
+                    .unwrap_or_else(Utc::now)
+                    .with_timezone(&Local)
+                    .fixed_offset(),
+            ));
         }
 
-        Ok(commits)
+        Ok(versions)
     }
 
-    fn should_ignore_commit(&self, commit: &git2::Commit, ignore_revs: &[String]) -> bool {
-        let commit_hash = commit.id().to_string();
+    /// Maps lines of the newest local version of a file back to HEAD,
+    /// reporting the uncommitted versions that changed them.
+    fn follow_local_changes(
+        &self,
+        file_path: &str,
+        lines: RangeInclusive<u32>,
+        versions: &[LocalVersion],
+        head_blob: Option<&IndexedBlob>,
+        dates: &DateRange,
+    ) -> Result<Vec<LocalLineHistory>> {
+        // Each version is compared with the one before it once for all lines
+        let mut version_hunks = Vec::with_capacity(versions.len());
+        for (i, version) in versions.iter().enumerate() {
+            let older_lines = match versions.get(i + 1) {
+                Some(older) => Some(older.lines()),
+                None => head_blob.map(IndexedBlob::lines),
+            };
+            let hunks = match older_lines {
+                Some(older_lines) => Some(self.diff_hunks(
+                    older_lines.content,
+                    &version.content,
+                    self.comparison(),
+                )?),
+                None => None,
+            };
+            version_hunks.push((older_lines, hunks));
+        }
+
+        let mut histories = Vec::new();
+        'lines: for start_line in lines {
+            let mut entries = Vec::new();
+            let mut line_number = start_line;
+
+            for (version, (older_lines, hunks)) in versions.iter().zip(&version_hunks) {
+                let origin = match (older_lines, hunks) {
+                    (Some(older_lines), Some(hunks)) => {
+                        self.map_line(hunks, *older_lines, version.lines(), line_number)
+                    }
+                    _ => LineOrigin::Added(None),
+                };
 
-        for ignore_rev in ignore_revs {
-            // Support both full hashes and abbreviated hashes
-            if commit_hash == *ignore_rev || commit_hash.starts_with(ignore_rev) {
-                return true;
+                let change_type = match origin {
+                    LineOrigin::Unchanged(older_line) => {
+                        line_number = older_line;
+                        continue;
+                    }
+                    LineOrigin::Changed(_) => ChangeType::Modified,
+                    LineOrigin::Added(_) => ChangeType::Created,
+                };
+
+                if dates.contains(version.timestamp.to_utc()) {
+                    let entry = LineEntry {
+                        commit_hash: git2::Oid::zero().to_string(),
+                        author: version.kind.to_string(),
+                        author_email: String::new(),
+                        timestamp: version.timestamp,
+                        committer: version.kind.to_string(),
+                        committer_email: String::new(),
+                        committer_timestamp: version.timestamp,
+                        co_authors: Vec::new(),
+                        subject: version.kind.description().to_string(),
+                        body: String::new(),
+                        trailers: Vec::new(),
+                        file_path: file_path.to_string(),
+                        line_number,
+                        content: version.lines().text(line_number).unwrap_or_default(),
+                        change_type,
+                        hunks: self.line_hunks(
+                            older_lines.map_or(&[], |lines| lines.content),
+                            &version.content,
+                            line_number,
+                            false,
+                        )?,
+                    };
+                    entries.push((version.kind, entry));
+                }
+
+                match origin {
+                    LineOrigin::Changed(older_line) => line_number = older_line,
+                    _ => {
+                        histories.push(LocalLineHistory {
+                            start_line,
+                            entries,
+                            committed_line: None,
+                        });
+                        continue 'lines;
+                    }
+                }
             }
+
+            histories.push(LocalLineHistory {
+                start_line,
+                entries,
+                committed_line: Some(line_number),
+            });
         }
 
-        false
+        Ok(histories)
     }
 
-    fn parse_git_date(&self, date_str: &str) -> Result<DateTime<Utc>> {
-        use chrono::TimeZone;
-
-        // Try ISO 8601 format first (most precise)
-        if let Ok(dt) = DateTime::parse_from_rfc3339(date_str) {
-            return Ok(dt.with_timezone(&Utc));
+    /// Resolves the revision history starts from, along with the lower end of
+    /// the range when a range was given.
+    fn resolve_revision(&self, rev: Option<&str>) -> Result<(git2::Commit<'_>, Option<git2::Oid>)> {
+        let Some(rev) = rev else {
+            return Ok((self.repository.head()?.peel_to_commit()?, None));
+        };
+
+        let revspec = self
+            .repository
+            .revparse(rev)
+            .map_err(|e| anyhow::anyhow!("Invalid revision '{}': {}", rev, e.message()))?;
+
+        if revspec.mode().contains(git2::RevparseMode::MERGE_BASE) {
+            return Err(anyhow::anyhow!(
+                "Symmetric difference ranges are not supported: {}",
+                rev
+            ));
         }
 
-        // Try RFC 2822 format
-        if let Ok(dt) = DateTime::parse_from_rfc2822(date_str) {
-            return Ok(dt.with_timezone(&Utc));
+        if revspec.mode().contains(git2::RevparseMode::RANGE) {
+            let to = match revspec.to() {
+                Some(to) => to.peel_to_commit()?,
+                None => self.repository.head()?.peel_to_commit()?,
+            };
+            let from = match revspec.from() {
+                Some(from) => Some(from.peel_to_commit()?.id()),
+                None => None,
+            };
+            return Ok((to, from));
         }
 
-        // Try custom RFC-like format that git sometimes uses
-        if let Ok(dt) = DateTime::parse_from_str(date_str, "%a, %d %b %Y %H:%M:%S %Z") {
-            return Ok(dt.with_timezone(&Utc));
+        let commit = revspec
+            .from()
+            .ok_or_else(|| anyhow::anyhow!("Invalid revision '{}'", rev))?
+            .peel_to_commit()?;
+        Ok((commit, None))
+    }
+
+    /// Walks the history from the starting commit and collects the changes to
+    /// each of `lines`, given as pairs of a line of the starting file and its
+    /// line number in the starting commit; every change is labelled with the
+    /// line it belongs to.
+    ///
+    /// Only commits a line is tracked in are diffed, and no more commits are
+    /// looked at once the origin of every line is found or `limit` is reached.
+    /// The topological order still makes libgit2 walk the whole commit graph
+    /// before the first commit comes out, so stopping early saves the diffs
+    /// but not that walk. Changes come out grouped by commit, newest commits
+    /// first.
+    fn find_commits_changing_lines(
+        &self,
+        starting_file: &StartingFile,
+        file_path: &str,
+        lines: &[(u32, u32)],
+        ignore_revs: &HashSet<git2::Oid>,
+        filter: &CommitFilter,
+        limit: Option<usize>,
+    ) -> Result<Vec<LineChange<'_>>> {
+        let start = starting_file.commit.id();
+        let mut changes = Vec::new();
+        let mut commit_count = 0;
+        let mut revwalk = self.repository.revwalk()?;
+        revwalk.push(start)?;
+        if let Some(hidden) = starting_file.hidden {
+            revwalk.hide(hidden)?;
+        }
+        // Children have to be visited before their parents so that the position
+        // of the tracked lines is known by the time a commit is reached; this
+        // sorting reads every commit up front.
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+        if self.first_parent {
+            revwalk.simplify_first_parent()?;
         }
 
-        // Try simple date format (YYYY-MM-DD)
-        if let Ok(dt) = chrono::NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
-            return Ok(Utc.from_utc_datetime(&dt.and_hms_opt(0, 0, 0).unwrap()));
+        let mut pending = PendingLines::default();
+        for &(start_line, line_number) in lines {
+            pending.track(
+                start,
+                start_line,
+                TrackedLine {
+                    file_path: file_path.to_string(),
+                    line_number,
+                },
+            );
         }
 
-        // Try datetime format (YYYY-MM-DD HH:MM:SS)
-        if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(date_str, "%Y-%m-%d %H:%M:%S") {
-            return Ok(Utc.from_utc_datetime(&dt));
+        for commit_oid in revwalk {
+            // Older changes are not needed once the limit is reached
+            if limit.is_some_and(|limit| commit_count >= limit) {
+                break;
+            }
+            // Every line has been followed back to where it came from
+            if pending.is_empty() {
+                break;
+            }
+
+            let commit_oid = commit_oid?;
+            let tracked = pending.tracked.remove(&commit_oid).unwrap_or_default();
+            let deleted = pending.deleted.remove(&commit_oid).unwrap_or_default();
+            if tracked.is_empty() && deleted.is_empty() {
+                continue;
+            }
+
+            let commit = self.repository.find_commit(commit_oid)?;
+            let mut diffs = CommitDiffs::default();
+            let mut commit_changes = Vec::new();
+            for (start_line, tracked) in tracked {
+                commit_changes.extend(self.follow_line(
+                    commit.clone(),
+                    start_line,
+                    tracked,
+                    ignore_revs,
+                    &mut pending,
+                    &mut diffs,
+                )?);
+            }
+            for (start_line, deleted) in deleted {
+                commit_changes.extend(self.follow_deleted_line(
+                    commit.clone(),
+                    start_line,
+                    deleted,
+                    ignore_revs,
+                    &mut pending,
+                    &mut diffs,
+                )?);
+            }
+            if self.use_cache
+                && diffs.new_mappings
+                && let Some(mappings) = &diffs.mappings
+            {
+                self.cache
+                    .store_line_mappings(commit_oid, self.line_mapping(), mappings);
+            }
+            if commit_changes.is_empty() {
+                continue;
+            }
+
+            // Check if this commit should be filtered by date or as a merge
+            if !filter.includes(&commit) {
+                continue;
+            }
+
+            commit_count += 1;
+            changes.extend(commit_changes);
         }
 
-        // If all else fails, return an error
-        Err(anyhow::anyhow!(
-            "Unable to parse date '{}'. Supported formats: ISO 8601 (YYYY-MM-DDTHH:MM:SSZ), RFC 2822, YYYY-MM-DD, YYYY-MM-DD HH:MM:SS",
-            date_str
-        ))
+        Ok(changes)
     }
 
-    fn should_filter_by_date(
-        &self,
-        commit: &git2::Commit,
-        since: Option<&str>,
-        until: Option<&str>,
-    ) -> Result<bool> {
-        let commit_time = commit.time();
-        let commit_timestamp =
-            DateTime::from_timestamp(commit_time.seconds(), 0).unwrap_or_else(|| Utc::now());
+    /// Maps the tracked line of `commit` into its parents and reports whether
+    /// the commit itself changed it.
+    ///
+    /// Changes made by ignored commits are passed through: the line keeps being
+    /// tracked in the parents, so it is attributed to the change before them.
+    fn follow_line<'repo>(
+        &'repo self,
+        commit: git2::Commit<'repo>,
+        start_line: u32,
+        tracked: TrackedLine,
+        ignore_revs: &HashSet<git2::Oid>,
+        pending: &mut PendingLines,
+        diffs: &mut CommitDiffs<'repo>,
+    ) -> Result<Option<LineChange<'repo>>> {
+        let origins = self.commit_changes_line(&tracked, &commit, diffs)?;
+
+        // If any parent already has the line as it is, the change (if any)
+        // happened on that side of the history, so only follow that parent
+        if let Some(unchanged) = origins
+            .iter()
+            .find(|origin| matches!(origin.origin, LineOrigin::Unchanged(_)))
+        {
+            if let Some(line) = unchanged.tracked_line() {
+                pending.track(unchanged.parent, start_line, line);
+            }
+            return Ok(None);
+        }
 
-        // Check since filter
-        if let Some(since_str) = since {
-            let since_date = self.parse_git_date(since_str)?;
-            if commit_timestamp < since_date {
-                return Ok(false);
+        for origin in &origins {
+            if let Some(line) = origin.tracked_line() {
+                pending.track(origin.parent, start_line, line);
             }
         }
 
-        // Check until filter
-        if let Some(until_str) = until {
-            let until_date = self.parse_git_date(until_str)?;
-            if commit_timestamp > until_date {
-                return Ok(false);
+        let ignored = ignore_revs.contains(&commit.id());
+        let content = self.line_content(&commit, &tracked.file_path, tracked.line_number, diffs)?;
+        let change_type = if origins
+            .iter()
+            .all(|origin| matches!(origin.origin, LineOrigin::Added(_)))
+        {
+            if self.find_copies
+                && is_distinctive(&content)
+                && let Some((parent, source, change_type)) =
+                    self.find_line_source(&commit, &tracked.file_path, &content, diffs)?
+            {
+                pending.track(parent, start_line, source);
+                if ignored {
+                    return Ok(None);
+                }
+                return Ok(Some(LineChange {
+                    start_line,
+                    commit,
+                    change_type,
+                    file_path: tracked.file_path,
+                    line_number: tracked.line_number,
+                    content,
+                }));
+            }
+
+            // Like `git blame --ignore-rev`, hand the line over to the closest
+            // line the ignored commit replaced
+            if ignored {
+                for origin in &origins {
+                    if let LineOrigin::Added(Some(line_number)) = origin.origin {
+                        pending.track(
+                            origin.parent,
+                            start_line,
+                            TrackedLine {
+                                file_path: origin.file_path.clone(),
+                                line_number,
+                            },
+                        );
+                    }
+                }
+                return Ok(None);
+            }
+
+            // The line may be the re-creation of one deleted earlier on
+            if is_distinctive(&content) {
+                for origin in &origins {
+                    pending.search_deleted(
+                        origin.parent,
+                        start_line,
+                        DeletedLine {
+                            file_path: origin.file_path.clone(),
+                            content: content.clone(),
+                            depth: 1,
+                        },
+                    );
+                }
             }
+            ChangeType::Created
+        } else {
+            ChangeType::Modified
+        };
+
+        if ignored {
+            return Ok(None);
         }
 
-        Ok(true)
+        Ok(Some(LineChange {
+            start_line,
+            commit,
+            change_type,
+            file_path: tracked.file_path,
+            line_number: tracked.line_number,
+            content,
+        }))
     }
 
-    fn commit_affects_file(&self, commit: &git2::Commit, file_path: &str) -> Result<bool> {
-        if let Some(tree) = commit.tree_id().into() {
-            let tree = self.repository.find_tree(tree)?;
-            return Ok(tree.get_path(Path::new(file_path)).is_ok());
+    /// Looks for the deletion of an earlier copy of a created line in `commit`.
+    fn follow_deleted_line<'repo>(
+        &'repo self,
+        commit: git2::Commit<'repo>,
+        start_line: u32,
+        deleted: DeletedLine,
+        ignore_revs: &HashSet<git2::Oid>,
+        pending: &mut PendingLines,
+        diffs: &mut CommitDiffs<'repo>,
+    ) -> Result<Option<LineChange<'repo>>> {
+        match self.commit_removes_line(&deleted, &commit, diffs)? {
+            LineRemoval::Removed(parent, removed_line) => {
+                if self.follow_deleted {
+                    pending.track(parent, start_line, removed_line.clone());
+                }
+                if ignore_revs.contains(&commit.id()) {
+                    return Ok(None);
+                }
+                Ok(Some(LineChange {
+                    start_line,
+                    commit,
+                    change_type: ChangeType::Deleted,
+                    file_path: removed_line.file_path,
+                    line_number: removed_line.line_number,
+                    content: deleted.content,
+                }))
+            }
+            LineRemoval::NotRemoved(parents) => {
+                // Without following deleted lines, the search is only worth
+                // the cost close to where the line was re-created
+                if !self.follow_deleted && deleted.depth >= DELETED_LINE_SEARCH_DEPTH {
+                    return Ok(None);
+                }
+                for (parent, file_path) in parents {
+                    pending.search_deleted(
+                        parent,
+                        start_line,
+                        DeletedLine {
+                            file_path,
+                            content: deleted.content.clone(),
+                            depth: deleted.depth + 1,
+                        },
+                    );
+                }
+                Ok(None)
+            }
         }
-        Ok(false)
     }
 
-    fn convert_commits_to_entries(&self, commits: Vec<git2::Commit>) -> Result<Vec<LineEntry>> {
-        let mut entries = Vec::new();
+    /// Gathers the revisions to ignore from the options and from ignore-revs files,
+    /// including the ones configured with `blame.ignoreRevsFile`.
+    ///
+    /// Like `git blame`, a `.git-blame-ignore-revs` file is only used when it
+    /// is configured.
+    fn collect_ignore_revs(&self, options: &HistoryOptions) -> Result<HashSet<git2::Oid>> {
+        let mut files = Vec::new();
+
+        if !options.no_ignore_revs_file {
+            let root = self
+                .repository
+                .workdir()
+                .unwrap_or_else(|| self.repository.path())
+                .to_path_buf();
+
+            let config = self.repository.config()?.snapshot()?;
+            let mut entries = config.multivar("blame.ignoreRevsFile", None)?;
+            while let Some(entry) = entries.next() {
+                match entry?.value() {
+                    // An empty value resets the list, like it does for git
+                    Some("") | None => files.clear(),
+                    Some(value) => {
+                        let home = std::env::var_os("HOME").map(PathBuf::from);
+                        files.push(root.join(expand_home(value, home.as_deref())))
+                    }
+                }
+            }
+        }
+
+        files.extend(
+            options
+                .ignore_revs_files
+                .iter()
+                .map(|file| self.base_dir.join(file)),
+        );
+
+        let mut ignore_revs = HashSet::new();
+        for rev in &options.ignore_revs {
+            ignore_revs.insert(self.resolve_ignore_rev(rev)?);
+        }
 
-        for commit in commits {
-            let entry = self.create_line_entry_from_commit(&commit, entries.is_empty())?;
-            entries.push(entry);
+        for file in files {
+            let content = std::fs::read_to_string(&file).map_err(|e| {
+                anyhow::anyhow!("Unable to read ignore-revs file {}: {}", file.display(), e)
+            })?;
+            // Like git, files only take full object names, which may be unknown
+            for rev in parse_ignore_revs(&content) {
+                let oid = parse_full_oid(&rev).ok_or_else(|| {
+                    anyhow::anyhow!("Invalid object name '{}' in {}", rev, file.display())
+                })?;
+                ignore_revs.insert(oid);
+            }
         }
 
-        Ok(entries)
+        Ok(ignore_revs)
+    }
+
+    /// Resolves a revision given to `--ignore-rev` to the commit it names.
+    fn resolve_ignore_rev(&self, rev: &str) -> Result<git2::Oid> {
+        let object = self
+            .repository
+            .revparse_single(rev)
+            .map_err(|e| match e.code() {
+                git2::ErrorCode::Ambiguous => {
+                    anyhow::anyhow!("Ambiguous revision '{}' given to --ignore-rev", rev)
+                }
+                git2::ErrorCode::NotFound => {
+                    anyhow::anyhow!("Unknown revision '{}' given to --ignore-rev", rev)
+                }
+                _ => anyhow::anyhow!("Invalid revision '{}' given to --ignore-rev: {}", rev, e),
+            })?;
+        let commit = object.peel_to_commit().map_err(|_| {
+            anyhow::anyhow!("Revision '{}' given to --ignore-rev is not a commit", rev)
+        })?;
+        Ok(commit.id())
+    }
+
+    /// Parses the `since` and `until` options once for the whole walk.
+    ///
+    /// Bare dates are read in the timezone of the options, or in local time
+    /// with the offset that applied on that date.
+    fn date_range(&self, options: &HistoryOptions) -> Result<DateRange> {
+        let timezone = options
+            .timezone
+            .as_deref()
+            .map(date::parse_timezone)
+            .transpose()?;
+        let parse = |input: &str| match timezone {
+            Some(timezone) => date::parse_date(input, Utc::now().with_timezone(&timezone)),
+            None => date::parse_date(input, Local::now()),
+        };
+
+        Ok(DateRange {
+            since: options.since.as_deref().map(parse).transpose()?,
+            until: options.until.as_deref().map(parse).transpose()?,
+        })
+    }
+
+    fn find_blob(&self, tree: &git2::Tree, file_path: &str) -> Result<Option<git2::Blob<'_>>> {
+        match tree.get_path(Path::new(file_path)) {
+            Ok(entry) if entry.kind() == Some(git2::ObjectType::Blob) => {
+                Ok(Some(self.repository.find_blob(entry.id())?))
+            }
+            Ok(_) => Ok(None),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
     }
 
+    /// Turns a change into an entry, mapping the names and emails in it to the
+    /// canonical ones when a mailmap is given.
     fn create_line_entry_from_commit(
         &self,
-        commit: &git2::Commit,
-        is_first_entry: bool,
+        change: LineChange,
+        mailmap: Option<&git2::Mailmap>,
     ) -> Result<LineEntry> {
-        let author = commit.author();
-        let timestamp =
-            DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_else(|| Utc::now());
+        let LineChange {
+            start_line: _,
+            commit,
+            change_type,
+            file_path,
+            line_number,
+            content,
+        } = change;
+        let (author, committer) = match mailmap {
+            Some(mailmap) => (
+                commit.author_with_mailmap(mailmap)?,
+                commit.committer_with_mailmap(mailmap)?,
+            ),
+            None => (commit.author().to_owned(), commit.committer().to_owned()),
+        };
+        let (subject, body, trailers) = parse_message(commit.message().unwrap_or(""));
+        let hunks = self.commit_hunks(&commit, &file_path, line_number, &change_type)?;
+        let mut co_authors = co_authors(&trailers);
+        if let Some(mailmap) = mailmap {
+            for co_author in &mut co_authors {
+                // A signature cannot have an empty name or email, so such
+                // co-authors are kept as recorded
+                if let Ok(signature) = git2::Signature::now(&co_author.name, &co_author.email)
+                    && let Ok(resolved) = mailmap.resolve_signature(&signature)
+                {
+                    co_author.name = resolved.name().unwrap_or(&co_author.name).to_string();
+                    co_author.email = resolved.email().unwrap_or(&co_author.email).to_string();
+                }
+            }
+        }
 
         Ok(LineEntry {
             commit_hash: commit.id().to_string(),
             author: author.name().unwrap_or("Unknown").to_string(),
-            timestamp,
-            message: commit.message().unwrap_or("").to_string(),
-            content: "".to_string(),
-            change_type: if is_first_entry {
-                ChangeType::Created
-            } else {
-                ChangeType::Modified
-            },
+            author_email: author.email().unwrap_or("").to_string(),
+            timestamp: signature_time(&author),
+            committer: committer.name().unwrap_or("Unknown").to_string(),
+            committer_email: committer.email().unwrap_or("").to_string(),
+            committer_timestamp: signature_time(&committer),
+            co_authors,
+            subject,
+            body,
+            trailers,
+            file_path,
+            line_number,
+            content,
+            change_type,
+            hunks,
         })
     }
 
-    fn sort_entries_chronologically(
+    /// Finds the hunk of the diff `commit` made to a file that holds the line
+    /// of a change, comparing with the first parent like `git log -p`.
+    ///
+    /// A deleted line is looked for on the side of the parent.
+    fn commit_hunks(
+        &self,
+        commit: &git2::Commit,
+        file_path: &str,
+        line_number: u32,
+        change_type: &ChangeType,
+    ) -> Result<Vec<DiffHunk>> {
+        if self.patch_context.is_none() {
+            return Ok(Vec::new());
+        }
+
+        let removed = *change_type == ChangeType::Deleted;
+        let old_blob = match commit.parents().next() {
+            Some(parent) if removed => self.find_blob(&parent.tree()?, file_path)?,
+            Some(parent) => self
+                .find_parent_blob(&parent, commit, file_path)?
+                .map(|(_, blob)| blob),
+            None => None,
+        };
+        let new_blob = self.find_blob(&commit.tree()?, file_path)?;
+
+        self.line_hunks(
+            old_blob.as_ref().map_or(&[], |blob| blob.content()),
+            new_blob.as_ref().map_or(&[], |blob| blob.content()),
+            line_number,
+            removed,
+        )
+    }
+
+    /// Diffs two versions of a file with the context lines of the patch
+    /// option, keeping the hunk that holds a line of the new version, or of the
+    /// old version for a `removed` line.
+    fn line_hunks(
         &self,
-        mut entries: Vec<LineEntry>,
-        sort_order: SortOrder,
-    ) -> Result<Vec<LineEntry>> {
-        match sort_order {
-            SortOrder::Desc => {
-                entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp)); // Newest first
+        old_content: &[u8],
+        new_content: &[u8],
+        line_number: u32,
+        removed: bool,
+    ) -> Result<Vec<DiffHunk>> {
+        let Some(context_lines) = self.patch_context else {
+            return Ok(Vec::new());
+        };
+
+        let comparison = self.comparison();
+        let mut options = git2::DiffOptions::new();
+        options
+            .context_lines(context_lines)
+            .ignore_whitespace(comparison == Comparison::IgnoreWhitespace)
+            .ignore_whitespace_change(comparison == Comparison::IgnoreSpaceChange);
+        let patch =
+            git2::Patch::from_buffers(old_content, None, new_content, None, Some(&mut options))?;
+
+        for hunk_idx in 0..patch.num_hunks() {
+            let (hunk, line_count) = patch.hunk(hunk_idx)?;
+            let (start, lines) = if removed {
+                (hunk.old_start(), hunk.old_lines())
+            } else {
+                (hunk.new_start(), hunk.new_lines())
+            };
+            if !(start..start + lines).contains(&line_number) {
+                continue;
             }
-            SortOrder::Asc => {
-                entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp)); // Oldest first
+
+            let mut diff_lines = Vec::with_capacity(line_count);
+            for line_idx in 0..line_count {
+                let line = patch.line_in_hunk(hunk_idx, line_idx)?;
+                let kind = match line.origin() {
+                    ' ' => DiffLineKind::Context,
+                    '+' => DiffLineKind::Added,
+                    '-' => DiffLineKind::Removed,
+                    // Markers for a missing newline at the end of the file
+                    _ => continue,
+                };
+                diff_lines.push(DiffLine {
+                    kind,
+                    content: String::from_utf8_lossy(trim_line_terminator(line.content()))
+                        .into_owned(),
+                });
             }
+
+            return Ok(vec![DiffHunk {
+                old_start: hunk.old_start(),
+                old_lines: hunk.old_lines(),
+                new_start: hunk.new_start(),
+                new_lines: hunk.new_lines(),
+                lines: diff_lines,
+            }]);
         }
-        Ok(entries)
+
+        Ok(Vec::new())
     }
 
-    fn commit_changes_line(
-        &self,
+    /// Returns the text of a line as it was in the given commit.
+    fn line_content<'repo>(
+        &'repo self,
+        commit: &git2::Commit<'repo>,
         file_path: &str,
-        _line_number: u32,
-        commit: &git2::Commit,
-    ) -> Result<bool> {
-        // For the first commit (no parents), assume it creates the line
-        if commit.parent_count() == 0 {
-            return Ok(true);
-        }
-
-        // For subsequent commits, check if the line content changed
-        // This is a simplified check - we assume if the file was modified in this commit,
-        // and the line exists, then it was potentially changed
-        let mut found_file_change = false;
-
-        for parent_commit in commit.parents() {
-            let diff = self.repository.diff_tree_to_tree(
-                Some(&parent_commit.tree()?),
-                Some(&commit.tree()?),
-                None,
-            )?;
-
-            diff.foreach(
-                &mut |delta, _progress| {
-                    if let Some(file) = delta.new_file().path() {
-                        if file == Path::new(file_path) {
-                            found_file_change = true;
+        line_number: u32,
+        diffs: &mut CommitDiffs<'repo>,
+    ) -> Result<String> {
+        let content = self
+            .commit_blob(commit, file_path, &mut diffs.blobs)?
+            .and_then(|blob| blob.lines().text(line_number))
+            .unwrap_or_default();
+        Ok(content)
+    }
+
+    /// Finds a file of `commit`, indexing its lines once for all the lines
+    /// looked at in the commit.
+    fn commit_blob<'repo, 'blobs>(
+        &'repo self,
+        commit: &git2::Commit<'repo>,
+        file_path: &str,
+        blobs: &'blobs mut HashMap<String, Option<IndexedBlob<'repo>>>,
+    ) -> Result<Option<&'blobs IndexedBlob<'repo>>> {
+        if !blobs.contains_key(file_path) {
+            let blob = self
+                .find_blob(&commit.tree()?, file_path)?
+                .map(IndexedBlob::new);
+            blobs.insert(file_path.to_string(), blob);
+        }
+        Ok(blobs[file_path].as_ref())
+    }
+
+    /// Maps the tracked line of `commit` back into each of its parents.
+    ///
+    /// Returns one [`ParentOrigin`] per parent; a root commit yields no origins.
+    fn commit_changes_line<'repo>(
+        &'repo self,
+        tracked: &TrackedLine,
+        commit: &git2::Commit<'repo>,
+        diffs: &mut CommitDiffs<'repo>,
+    ) -> Result<Vec<ParentOrigin>> {
+        let mappings = diffs.mappings.get_or_insert_with(|| {
+            self.use_cache
+                .then(|| self.cache.line_mappings(commit.id(), self.line_mapping()))
+                .flatten()
+                .unwrap_or_default()
+        });
+        if let Some(origins) = mappings
+            .get(&tracked.file_path)
+            .and_then(|lines| lines.get(&tracked.line_number))
+        {
+            return Ok(origins.clone());
+        }
+
+        let blob = self
+            .commit_blob(commit, &tracked.file_path, &mut diffs.blobs)?
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "File {} is missing from commit {}",
+                    tracked.file_path,
+                    commit.id()
+                )
+            })?;
+        if !diffs.changes.contains_key(&tracked.file_path) {
+            let changes = self.file_changes(commit, &tracked.file_path, &blob.blob)?;
+            diffs.changes.insert(tracked.file_path.clone(), changes);
+        }
+        let changes = &diffs.changes[&tracked.file_path];
+        let is_merge = changes.parents.len() > 1;
+
+        let origins = changes
+            .parents
+            .iter()
+            .map(|parent_file| ParentOrigin {
+                parent: parent_file.parent,
+                file_path: parent_file.file_path.clone(),
+                origin: match &parent_file.diff {
+                    Some((parent_blob, hunks)) => {
+                        let origin = self.map_line(
+                            hunks,
+                            parent_blob.lines(),
+                            blob.lines(),
+                            tracked.line_number,
+                        );
+                        match origin {
+                            // The diff against this side of a merge may pair the
+                            // line differently while the side already had it
+                            LineOrigin::Changed(parent_line)
+                                if is_merge
+                                    && self.same_line(
+                                        &parent_blob.lines().text(parent_line).unwrap_or_default(),
+                                        &blob.lines().text(tracked.line_number).unwrap_or_default(),
+                                    ) =>
+                            {
+                                LineOrigin::Unchanged(parent_line)
+                            }
+                            origin => origin,
                         }
                     }
-                    true
+                    None => LineOrigin::Added(None),
                 },
-                None,
-                None,
-                None,
-            )?;
+            })
+            .collect::<Vec<_>>();
+
+        if self.use_cache {
+            diffs
+                .mappings
+                .get_or_insert_default()
+                .entry(tracked.file_path.clone())
+                .or_default()
+                .insert(tracked.line_number, origins.clone());
+            diffs.new_mappings = true;
+        }
+        Ok(origins)
+    }
 
-            if found_file_change {
-                return Ok(true);
-            }
+    /// The parents of a commit whose history is followed: only the first one
+    /// when following the first parent.
+    fn parents<'repo>(&self, commit: &git2::Commit<'repo>) -> Vec<git2::Commit<'repo>> {
+        let count = if self.first_parent { 1 } else { usize::MAX };
+        commit.parents().take(count).collect()
+    }
+
+    /// Compares a file of `commit` with the same file in each parent.
+    fn file_changes<'repo>(
+        &'repo self,
+        commit: &git2::Commit<'repo>,
+        file_path: &str,
+        blob: &git2::Blob,
+    ) -> Result<FileChanges<'repo>> {
+        let mut parents = Vec::new();
+        for parent_commit in self.parents(commit) {
+            let parent_file = match self.find_parent_blob(&parent_commit, commit, file_path)? {
+                Some((parent_path, parent_blob)) => {
+                    // Same blob, same lines: nothing to diff
+                    let hunks = if parent_blob.id() == blob.id() {
+                        Vec::new()
+                    } else {
+                        self.blob_hunks(&parent_blob, blob, self.comparison())?
+                    };
+                    ParentFile {
+                        parent: parent_commit.id(),
+                        file_path: parent_path,
+                        diff: Some((IndexedBlob::new(parent_blob), hunks)),
+                    }
+                }
+                None => ParentFile {
+                    parent: parent_commit.id(),
+                    file_path: file_path.to_string(),
+                    diff: None,
+                },
+            };
+            parents.push(parent_file);
         }
 
-        Ok(false)
+        Ok(FileChanges { parents })
     }
-}
 
-impl LineHistoryProvider for GitAdapter {
-    fn get_line_history(
+    /// Finds the blob of a file of `commit` in one of its parents, following
+    /// renames and moves when the parent has nothing at the same path.
+    fn find_parent_blob(
         &self,
+        parent_commit: &git2::Commit,
+        commit: &git2::Commit,
         file_path: &str,
-        line_number: u32,
-        sort_order: SortOrder,
-        ignore_revs: &[String],
-        since: Option<&str>,
-        until: Option<&str>,
-    ) -> Result<LineHistory> {
-        // Use full history extraction for multiple commits
-        let entries = self.extract_full_line_history(
-            file_path,
-            line_number,
-            sort_order,
-            ignore_revs,
-            since,
-            until,
-        )?;
-
-        let mut history = LineHistory::new(file_path.to_string(), line_number);
-        for entry in entries {
-            history.add_entry(entry);
+    ) -> Result<Option<(String, git2::Blob<'_>)>> {
+        let parent_tree = parent_commit.tree()?;
+        if let Some(blob) = self.find_blob(&parent_tree, file_path)? {
+            return Ok(Some((file_path.to_string(), blob)));
         }
 
-        Ok(history)
+        let old_path = self
+            .renames(parent_commit, commit)?
+            .into_iter()
+            .find(|(_, new_path)| new_path == file_path)
+            .map(|(old_path, _)| old_path);
+
+        match old_path {
+            Some(old_path) => Ok(self
+                .find_blob(&parent_tree, &old_path)?
+                .map(|blob| (old_path, blob))),
+            None => Ok(None),
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-    use tempfile::TempDir;
+    /// Lists the files `commit` renamed since `parent_commit`, as pairs of old
+    /// and new path, going through the on-disk cache.
+    fn renames(
+        &self,
+        parent_commit: &git2::Commit,
+        commit: &git2::Commit,
+    ) -> Result<Vec<(String, String)>> {
+        if self.use_cache
+            && let Some(renames) = self.cache.renames(parent_commit.id(), commit.id())
+        {
+            return Ok(renames);
+        }
 
-    fn setup_test_repo() -> Result<TempDir> {
-        let temp_dir = TempDir::new()?;
-        let repo_path = temp_dir.path();
+        let mut diff = self.repository.diff_tree_to_tree(
+            Some(&parent_commit.tree()?),
+            Some(&commit.tree()?),
+            None,
+        )?;
+        let mut find_options = git2::DiffFindOptions::new();
+        find_options.renames(true);
+        diff.find_similar(Some(&mut find_options))?;
+
+        let renames: Vec<(String, String)> = diff
+            .deltas()
+            .filter(|delta| delta.status() == git2::Delta::Renamed)
+            .filter_map(|delta| {
+                let old_path = delta.old_file().path()?.to_string_lossy().into_owned();
+                let new_path = delta.new_file().path()?.to_string_lossy().into_owned();
+                Some((old_path, new_path))
+            })
+            .collect();
+
+        if self.use_cache {
+            self.cache
+                .store_renames(parent_commit.id(), commit.id(), &renames);
+        }
+        Ok(renames)
+    }
 
-        // Initialize git repository
-        let repo = Repository::init(repo_path)?;
+    /// Finds another file changed by `commit` that the given line was moved or
+    /// copied from, preferring files the line was removed from.
+    fn find_line_source(
+        &self,
+        commit: &git2::Commit,
+        file_path: &str,
+        content: &str,
+        diffs: &mut CommitDiffs,
+    ) -> Result<Option<(git2::Oid, TrackedLine, ChangeType)>> {
+        if diffs.sources.is_none() {
+            diffs.sources = Some(self.source_files(commit)?);
+        }
 
-        // Configure git user
-        let mut config = repo.config()?;
-        config.set_str("user.name", "Test User")?;
-        config.set_str("user.email", "test@example.com")?;
+        let mut copy_source = None;
+        for source in diffs.sources.iter().flatten() {
+            if source.file_path == file_path {
+                continue;
+            }
 
-        // Create initial file
-        let file_path = repo_path.join("test.txt");
-        fs::write(&file_path, "line 1\nline 2\nline 3\n")?;
+            if let Some(&line_number) = source.removed.get(content.as_bytes()) {
+                let line = TrackedLine {
+                    file_path: source.file_path.clone(),
+                    line_number,
+                };
+                return Ok(Some((source.parent, line, ChangeType::Moved)));
+            }
 
-        // Add and commit
-        let mut index = repo.index()?;
-        index.add_path(Path::new("test.txt"))?;
-        index.write()?;
+            if copy_source.is_none()
+                && let Some(&line_number) = source.lines.get(content.as_bytes())
+            {
+                let line = TrackedLine {
+                    file_path: source.file_path.clone(),
+                    line_number,
+                };
+                copy_source = Some((source.parent, line, ChangeType::Copied));
+            }
+        }
 
-        let tree_id = index.write_tree()?;
-        let tree = repo.find_tree(tree_id)?;
-        let signature = git2::Signature::now("Test User", "test@example.com")?;
+        Ok(copy_source)
+    }
 
-        repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            "Initial commit",
-            &tree,
-            &[],
-        )?;
+    /// Lists the files `commit` modified or deleted, compared with each parent,
+    /// once for all the lines of the commit looked up in them.
+    fn source_files(&self, commit: &git2::Commit) -> Result<Vec<SourceFile>> {
+        let tree = commit.tree()?;
+        let mut sources = Vec::new();
+
+        for parent_commit in self.parents(commit) {
+            let parent_tree = parent_commit.tree()?;
+            let diff = self
+                .repository
+                .diff_tree_to_tree(Some(&parent_tree), Some(&tree), None)?;
+
+            for delta in diff.deltas() {
+                if !matches!(delta.status(), git2::Delta::Modified | git2::Delta::Deleted) {
+                    continue;
+                }
+                let Some(source_path) = delta.old_file().path() else {
+                    continue;
+                };
+                let source_path = source_path.to_string_lossy().into_owned();
+                let Some(source_blob) = self.find_blob(&parent_tree, &source_path)? else {
+                    continue;
+                };
+
+                let lines = all_lines(source_blob.content());
+                let removed = match self.find_blob(&tree, &source_path)? {
+                    Some(blob) => self.removed_lines(&source_blob, &blob)?,
+                    None => lines.clone(),
+                };
+                sources.push(SourceFile {
+                    parent: parent_commit.id(),
+                    file_path: source_path,
+                    removed,
+                    lines,
+                });
+            }
+        }
 
-        Ok(temp_dir)
+        Ok(sources)
     }
 
-    fn setup_test_repo_with_multiple_commits() -> Result<TempDir> {
-        let temp_dir = TempDir::new()?;
-        let repo_path = temp_dir.path();
+    /// Checks whether `commit` removed a line with the given content from the file.
+    fn commit_removes_line(
+        &self,
+        deleted: &DeletedLine,
+        commit: &git2::Commit,
+        diffs: &mut CommitDiffs,
+    ) -> Result<LineRemoval> {
+        if !diffs.removals.contains_key(&deleted.file_path) {
+            let removals = self.file_removals(commit, &deleted.file_path)?;
+            diffs.removals.insert(deleted.file_path.clone(), removals);
+        }
+
+        let mut removal = None;
+        let mut parents = Vec::new();
+        for removals in &diffs.removals[&deleted.file_path] {
+            let Some(parent_path) = &removals.file_path else {
+                parents.push((removals.parent, deleted.file_path.clone()));
+                continue;
+            };
+
+            // Nothing happened to the file on this side of the history
+            if removals.unchanged {
+                return Ok(LineRemoval::NotRemoved(vec![(
+                    removals.parent,
+                    parent_path.clone(),
+                )]));
+            }
+
+            match removals.removed.get(deleted.content.as_bytes()) {
+                Some(&line_number) => {
+                    removal.get_or_insert((
+                        removals.parent,
+                        TrackedLine {
+                            file_path: parent_path.clone(),
+                            line_number,
+                        },
+                    ));
+                }
+                None => parents.push((removals.parent, parent_path.clone())),
+            }
+        }
+
+        // A merge only removed the line if every side still had it; otherwise
+        // it was removed on a side that did not have it anymore
+        match removal {
+            Some((parent, removed_line)) if parents.is_empty() => {
+                Ok(LineRemoval::Removed(parent, removed_line))
+            }
+            _ => Ok(LineRemoval::NotRemoved(parents)),
+        }
+    }
+
+    /// Finds the lines `commit` removed from a file, compared with each parent.
+    fn file_removals(&self, commit: &git2::Commit, file_path: &str) -> Result<Vec<ParentRemovals>> {
+        let blob = self.find_blob(&commit.tree()?, file_path)?;
+
+        let mut removals = Vec::new();
+        for parent_commit in self.parents(commit) {
+            let parent_file = match &blob {
+                Some(_) => self.find_parent_blob(&parent_commit, commit, file_path)?,
+                None => self
+                    .find_blob(&parent_commit.tree()?, file_path)?
+                    .map(|parent_blob| (file_path.to_string(), parent_blob)),
+            };
+
+            let Some((parent_path, parent_blob)) = parent_file else {
+                removals.push(ParentRemovals {
+                    parent: parent_commit.id(),
+                    file_path: None,
+                    unchanged: false,
+                    removed: HashMap::new(),
+                });
+                continue;
+            };
+
+            let unchanged = blob.as_ref().map(|blob| blob.id()) == Some(parent_blob.id());
+            let removed = match &blob {
+                _ if unchanged => HashMap::new(),
+                Some(blob) => self.removed_lines(&parent_blob, blob)?,
+                // The whole file was deleted
+                None => all_lines(parent_blob.content()),
+            };
+            removals.push(ParentRemovals {
+                parent: parent_commit.id(),
+                file_path: Some(parent_path),
+                unchanged,
+                removed,
+            });
+        }
+
+        Ok(removals)
+    }
+
+    /// Collects the lines removed between two blobs, mapping the text of each
+    /// to the first line in the old blob it was removed from.
+    fn removed_lines(
+        &self,
+        old_blob: &git2::Blob,
+        new_blob: &git2::Blob,
+    ) -> Result<HashMap<Vec<u8>, u32>> {
+        let hunks = self.blob_hunks(old_blob, new_blob, Comparison::Exact)?;
+        let old_lines: Vec<&[u8]> = old_blob
+            .content()
+            .split(|&byte| byte == b'\n')
+            .take(count_lines(old_blob.content()) as usize)
+            .collect();
+
+        let mut removed = HashMap::new();
+        for hunk in hunks {
+            for line_number in hunk.old_start..hunk.old_start + hunk.old_lines {
+                if let Some(line) = old_lines.get(line_number as usize - 1) {
+                    removed
+                        .entry(trim_line_terminator(line).to_vec())
+                        .or_insert(line_number);
+                }
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Maps a line of `new_content` back to `old_content` through the hunks
+    /// between them, honoring the whitespace settings.
+    fn map_line(
+        &self,
+        hunks: &[HunkRange],
+        old_lines: Lines,
+        new_lines: Lines,
+        line_number: u32,
+    ) -> LineOrigin {
+        let origin = map_line_to_parent(hunks, line_number);
+        if !self.ignore_blank_lines {
+            return origin;
+        }
+
+        let Some(paired) = pair_skipping_blank_lines(hunks, old_lines, new_lines, line_number)
+        else {
+            return origin;
+        };
+        match paired {
+            LineOrigin::Changed(old_line)
+                if self.same_line(
+                    &old_lines.text(old_line).unwrap_or_default(),
+                    &new_lines.text(line_number).unwrap_or_default(),
+                ) =>
+            {
+                LineOrigin::Unchanged(old_line)
+            }
+            paired => paired,
+        }
+    }
+
+    /// Whether two lines are the same once the ignored whitespace is removed.
+    fn same_line(&self, old_line: &str, new_line: &str) -> bool {
+        if self.ignore_whitespace {
+            old_line
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .eq(new_line.chars().filter(|c| !c.is_whitespace()))
+        } else if self.ignore_space_change {
+            collapse_whitespace(old_line) == collapse_whitespace(new_line)
+        } else {
+            old_line == new_line
+        }
+    }
+
+    /// How lines are mapped into the parents of a commit, as a cache key.
+    fn line_mapping(&self) -> LineMapping {
+        LineMapping {
+            comparison: self.comparison(),
+            ignore_blank_lines: self.ignore_blank_lines,
+            first_parent: self.first_parent,
+        }
+    }
+
+    /// How lines are compared given the whitespace settings.
+    fn comparison(&self) -> Comparison {
+        if self.ignore_whitespace {
+            Comparison::IgnoreWhitespace
+        } else if self.ignore_space_change {
+            Comparison::IgnoreSpaceChange
+        } else {
+            Comparison::Exact
+        }
+    }
+
+    /// Diffs two blobs, going through the on-disk cache.
+    fn blob_hunks(
+        &self,
+        old_blob: &git2::Blob,
+        new_blob: &git2::Blob,
+        comparison: Comparison,
+    ) -> Result<Vec<HunkRange>> {
+        if !self.use_cache {
+            return self.diff_hunks(old_blob.content(), new_blob.content(), comparison);
+        }
+        if let Some(hunks) = self.cache.hunks(old_blob.id(), new_blob.id(), comparison) {
+            return Ok(hunks);
+        }
+
+        let hunks = self.diff_hunks(old_blob.content(), new_blob.content(), comparison)?;
+        self.cache
+            .store_hunks(old_blob.id(), new_blob.id(), comparison, &hunks);
+        Ok(hunks)
+    }
+
+    fn diff_hunks(
+        &self,
+        old_content: &[u8],
+        new_content: &[u8],
+        comparison: Comparison,
+    ) -> Result<Vec<HunkRange>> {
+        let mut options = git2::DiffOptions::new();
+        options
+            .context_lines(0)
+            .ignore_whitespace(comparison == Comparison::IgnoreWhitespace)
+            .ignore_whitespace_change(comparison == Comparison::IgnoreSpaceChange);
+
+        let patch =
+            git2::Patch::from_buffers(old_content, None, new_content, None, Some(&mut options))?;
+
+        let mut hunks = Vec::with_capacity(patch.num_hunks());
+        for hunk_idx in 0..patch.num_hunks() {
+            let (hunk, _) = patch.hunk(hunk_idx)?;
+            hunks.push(HunkRange {
+                old_start: hunk.old_start(),
+                old_lines: hunk.old_lines(),
+                new_start: hunk.new_start(),
+                new_lines: hunk.new_lines(),
+            });
+        }
+
+        Ok(hunks)
+    }
+}
+
+/// A commit that changed the tracked line, with the line's file and text in it.
+///
+/// For deletions, the line is the one removed from the parent.
+struct LineChange<'repo> {
+    /// Line of the starting file the change belongs to
+    start_line: u32,
+    commit: git2::Commit<'repo>,
+    change_type: ChangeType,
+    file_path: String,
+    line_number: u32,
+    content: String,
+}
+
+/// A version of a file that is not committed yet.
+struct LocalVersion {
+    kind: LocalChange,
+    content: Vec<u8>,
+    index: LineIndex,
+    /// When the version was last changed, in local time
+    timestamp: DateTime<FixedOffset>,
+}
+
+impl LocalVersion {
+    fn new(kind: LocalChange, content: Vec<u8>, timestamp: DateTime<FixedOffset>) -> Self {
+        let index = LineIndex::new(&content);
+        Self {
+            kind,
+            content,
+            index,
+            timestamp,
+        }
+    }
+
+    fn lines(&self) -> Lines<'_> {
+        self.index.lines(&self.content)
+    }
+}
+
+/// The file history starts from.
+struct StartingFile<'repo> {
+    commit: git2::Commit<'repo>,
+    /// Lower end of the range of commits to look at
+    hidden: Option<git2::Oid>,
+    /// The file in `commit`, unless only the index has it
+    blob: Option<IndexedBlob<'repo>>,
+    local_versions: Vec<LocalVersion>,
+}
+
+impl StartingFile<'_> {
+    /// The newest version of the file, which line numbers refer to: the one on
+    /// disk when there is one.
+    fn newest_lines(&self) -> Option<Lines<'_>> {
+        self.local_versions
+            .first()
+            .map(LocalVersion::lines)
+            .or(self.blob.as_ref().map(IndexedBlob::lines))
+    }
+}
+
+/// An entry for one line of the starting file.
+struct SourcedEntry {
+    start_line: u32,
+    source: ChangeSource,
+    key: SortKey,
+    entry: LineEntry,
+}
+
+/// What entries are ordered by.
+#[derive(Debug, Clone, Copy)]
+struct SortKey {
+    /// Position of the change in the ancestry, 0 being the newest
+    rank: usize,
+    author_time: DateTime<FixedOffset>,
+    committer_time: DateTime<FixedOffset>,
+}
+
+/// What an entry was made from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ChangeSource {
+    Local(LocalChange),
+    Commit(git2::Oid),
+}
+
+/// The uncommitted changes to a line, and where the line is in HEAD.
+struct LocalLineHistory {
+    /// Line of the starting file
+    start_line: u32,
+    entries: Vec<(LocalChange, LineEntry)>,
+    /// Line number in HEAD, unless the line does not exist there
+    committed_line: Option<u32>,
+}
+
+/// Where an uncommitted version of a file lives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum LocalChange {
+    WorkingTree,
+    Staged,
+}
+
+impl LocalChange {
+    fn description(self) -> &'static str {
+        match self {
+            LocalChange::WorkingTree => "Changes in the working tree",
+            LocalChange::Staged => "Changes staged in the index",
+        }
+    }
+}
+
+impl std::fmt::Display for LocalChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LocalChange::WorkingTree => write!(f, "Not Committed Yet"),
+            LocalChange::Staged => write!(f, "Staged"),
+        }
+    }
+}
+
+/// Where the tracked line is in a particular commit.
+#[derive(Debug, Clone, PartialEq)]
+struct TrackedLine {
+    file_path: String,
+    line_number: u32,
+}
+
+/// A created line whose earlier deletion is looked for in a particular commit.
+#[derive(Debug, Clone, PartialEq)]
+struct DeletedLine {
+    file_path: String,
+    content: String,
+    /// Number of commits looked at so far, this one included
+    depth: u32,
+}
+
+/// Lines still to be looked at, keyed by the commit they belong to.
+#[derive(Default)]
+struct PendingLines {
+    tracked: HashMap<git2::Oid, BTreeMap<u32, TrackedLine>>,
+    deleted: HashMap<git2::Oid, BTreeMap<u32, DeletedLine>>,
+}
+
+impl PendingLines {
+    /// Tracks a line of the starting file in `commit`, unless it already is.
+    fn track(&mut self, commit: git2::Oid, start_line: u32, tracked: TrackedLine) {
+        self.tracked
+            .entry(commit)
+            .or_default()
+            .entry(start_line)
+            .or_insert(tracked);
+    }
+
+    /// Looks for the deletion of an earlier copy of a line of the starting
+    /// file in `commit`, unless it already is.
+    fn search_deleted(&mut self, commit: git2::Oid, start_line: u32, deleted: DeletedLine) {
+        self.deleted
+            .entry(commit)
+            .or_default()
+            .entry(start_line)
+            .or_insert(deleted);
+    }
+
+    /// Whether no line is left to follow.
+    fn is_empty(&self) -> bool {
+        self.tracked.is_empty() && self.deleted.is_empty()
+    }
+}
+
+/// Bounds on the commit time of the changes to report.
+struct DateRange {
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+}
+
+impl DateRange {
+    fn contains(&self, timestamp: DateTime<Utc>) -> bool {
+        self.since.is_none_or(|since| timestamp >= since)
+            && self.until.is_none_or(|until| timestamp <= until)
+    }
+}
+
+/// Which commits the changes found are reported for.
+struct CommitFilter {
+    dates: DateRange,
+    no_merges: bool,
+}
+
+impl CommitFilter {
+    fn includes(&self, commit: &git2::Commit) -> bool {
+        self.dates.contains(commit_timestamp(commit))
+            && !(self.no_merges && commit.parent_count() > 1)
+    }
+}
+
+/// Diffs of the files of one commit against its parents, computed once for
+/// all the lines looked at in that commit.
+#[derive(Default)]
+struct CommitDiffs<'repo> {
+    /// Files of the commit, unless it does not have them
+    blobs: HashMap<String, Option<IndexedBlob<'repo>>>,
+    changes: HashMap<String, FileChanges<'repo>>,
+    removals: HashMap<String, Vec<ParentRemovals>>,
+    /// Lines of the commit mapped into its parents, once read from the cache
+    mappings: Option<LineMappings>,
+    /// Whether lines were mapped that the cache does not hold yet
+    new_mappings: bool,
+    /// Files lines may have been moved or copied from, once looked up
+    sources: Option<Vec<SourceFile>>,
+}
+
+/// How a file of a commit differs from the file in each parent.
+struct FileChanges<'repo> {
+    parents: Vec<ParentFile<'repo>>,
+}
+
+/// A file of a commit as it is in one of the parents.
+struct ParentFile<'repo> {
+    parent: git2::Oid,
+    /// Path of the file in the parent, which differs after a rename
+    file_path: String,
+    /// The file in the parent and the hunks turning it into the commit's,
+    /// unless the parent does not have it
+    diff: Option<(IndexedBlob<'repo>, Vec<HunkRange>)>,
+}
+
+/// A file a commit modified or deleted, compared with one of the parents.
+struct SourceFile {
+    parent: git2::Oid,
+    /// Path of the file in the parent
+    file_path: String,
+    /// Text of the lines the commit removed, with where they were in the parent
+    removed: HashMap<Vec<u8>, u32>,
+    /// Text of every line of the file in the parent, with its first line
+    lines: HashMap<Vec<u8>, u32>,
+}
+
+/// The lines a commit removed from a file, compared with one of the parents.
+struct ParentRemovals {
+    parent: git2::Oid,
+    /// Path of the file in the parent, unless the parent does not have it
+    file_path: Option<String>,
+    /// The file is the same in the parent
+    unchanged: bool,
+    /// Text of the removed lines, with the first line of the parent they
+    /// were removed from
+    removed: HashMap<Vec<u8>, u32>,
+}
+
+/// Outcome of looking for the deletion of a line in a commit.
+enum LineRemoval {
+    /// The line was removed; this is where it was in that parent.
+    Removed(git2::Oid, TrackedLine),
+    /// The line was not removed; keep looking in these parents at these paths.
+    NotRemoved(Vec<(git2::Oid, String)>),
+}
+
+/// The tracked line of a commit as seen from one of its parents.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ParentOrigin {
+    #[serde(with = "oid_name")]
+    parent: git2::Oid,
+    /// Path of the file in the parent, which differs after a rename
+    file_path: String,
+    origin: LineOrigin,
+}
+
+impl ParentOrigin {
+    /// Where to keep tracking the line in the parent, unless it was added.
+    fn tracked_line(&self) -> Option<TrackedLine> {
+        match self.origin {
+            LineOrigin::Unchanged(line_number) | LineOrigin::Changed(line_number) => {
+                Some(TrackedLine {
+                    file_path: self.file_path.clone(),
+                    line_number,
+                })
+            }
+            LineOrigin::Added(_) => None,
+        }
+    }
+}
+
+/// How the tracked line of a commit relates to one of its parents.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum LineOrigin {
+    /// The parent has the same line, at the given line number.
+    Unchanged(u32),
+    /// The line was rewritten; its previous version is at the given line number.
+    Changed(u32),
+    /// The line does not exist in the parent; the last line of the parent it
+    /// was written over, if any, is the closest guess of where it came from.
+    Added(Option<u32>),
+}
+
+/// Line ranges of a zero-context diff hunk, as reported by libgit2.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct HunkRange {
+    old_start: u32,
+    old_lines: u32,
+    new_start: u32,
+    new_lines: u32,
+}
+
+/// Stores object ids by their hexadecimal name.
+mod oid_name {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        oid: &git2::Oid,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(oid)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<git2::Oid, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        git2::Oid::from_str(&name).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Maps a 1-based line of the new side of a diff back to the old side.
+///
+/// Lines inside a hunk are paired with the old lines in order; lines past the
+/// end of the old side were added by the change.
+fn map_line_to_parent(hunks: &[HunkRange], line_number: u32) -> LineOrigin {
+    let mut offset: i64 = 0;
+
+    for hunk in hunks {
+        if hunk.new_lines == 0 {
+            // Pure deletion: `new_start` is the line right before the removed block
+            if hunk.new_start < line_number {
+                offset += i64::from(hunk.old_lines);
+                continue;
+            }
+            break;
+        }
+
+        if line_number < hunk.new_start {
+            break;
+        }
+
+        if line_number < hunk.new_start + hunk.new_lines {
+            let index = line_number - hunk.new_start;
+            return if index < hunk.old_lines {
+                LineOrigin::Changed(hunk.old_start + index)
+            } else if hunk.old_lines > 0 {
+                LineOrigin::Added(Some(hunk.old_start + hunk.old_lines - 1))
+            } else {
+                LineOrigin::Added(None)
+            };
+        }
+
+        offset += i64::from(hunk.old_lines) - i64::from(hunk.new_lines);
+    }
+
+    LineOrigin::Unchanged((i64::from(line_number) + offset) as u32)
+}
+
+/// Adds the change one commit made to the next line of a range to its entry.
+fn merge_entry(entry: &mut LineEntry, next: LineEntry) {
+    entry.content.push('\n');
+    entry.content.push_str(&next.content);
+    for hunk in next.hunks {
+        if !entry.hunks.contains(&hunk) {
+            entry.hunks.push(hunk);
+        }
+    }
+    if entry.change_type != next.change_type {
+        entry.change_type = ChangeType::Modified;
+    }
+}
+
+fn commit_timestamp(commit: &git2::Commit) -> DateTime<Utc> {
+    DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_else(Utc::now)
+}
+
+/// The time of a signature, in the timezone it was recorded in.
+fn signature_time(signature: &git2::Signature) -> DateTime<FixedOffset> {
+    let when = signature.when();
+    let offset = FixedOffset::east_opt(when.offset_minutes() * 60)
+        .unwrap_or(FixedOffset::east_opt(0).unwrap());
+    DateTime::from_timestamp(when.seconds(), 0)
+        .unwrap_or_else(Utc::now)
+        .with_timezone(&offset)
+}
+
+/// Splits a commit message into its subject, body and trailers, like the
+/// `%s`, `%b` and `%(trailers)` placeholders of `git log`.
+///
+/// The subject is the first paragraph joined into one line. Trailers are only
+/// recognized in the last paragraph after the subject, and only when every
+/// line of it is a trailer or the continuation of one.
+fn parse_message(message: &str) -> (String, String, Vec<Trailer>) {
+    let mut paragraphs: Vec<Vec<&str>> = Vec::new();
+    let mut paragraph = Vec::new();
+    for line in message.lines() {
+        if line.trim().is_empty() {
+            if !paragraph.is_empty() {
+                paragraphs.push(std::mem::take(&mut paragraph));
+            }
+        } else {
+            paragraph.push(line.trim_end());
+        }
+    }
+    if !paragraph.is_empty() {
+        paragraphs.push(paragraph);
+    }
+
+    let mut paragraphs = paragraphs.into_iter();
+    let subject = paragraphs
+        .next()
+        .map(|lines| {
+            lines
+                .iter()
+                .map(|line| line.trim())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default();
+    let mut rest: Vec<Vec<&str>> = paragraphs.collect();
+    let trailers = match rest.last().and_then(|lines| parse_trailers(lines)) {
+        Some(trailers) => {
+            rest.pop();
+            trailers
+        }
+        None => Vec::new(),
+    };
+    let body = rest
+        .iter()
+        .map(|lines| lines.join("\n"))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    (subject, body, trailers)
+}
+
+/// Parses a paragraph of `Key: value` lines, folding indented lines into the
+/// value before them.
+fn parse_trailers(lines: &[&str]) -> Option<Vec<Trailer>> {
+    let mut trailers: Vec<Trailer> = Vec::new();
+    for line in lines {
+        if line.starts_with(char::is_whitespace) {
+            let trailer = trailers.last_mut()?;
+            trailer.value.push(' ');
+            trailer.value.push_str(line.trim());
+            continue;
+        }
+
+        let (key, value) = line.split_once(':')?;
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return None;
+        }
+        trailers.push(Trailer {
+            key: key.to_string(),
+            value: value.trim().to_string(),
+        });
+    }
+    Some(trailers)
+}
+
+/// Collects the people credited with `Co-authored-by:` trailers; a trailer
+/// without an `<email>` credits its whole value as the name.
+fn co_authors(trailers: &[Trailer]) -> Vec<Person> {
+    trailers
+        .iter()
+        .filter(|trailer| trailer.key.eq_ignore_ascii_case("co-authored-by"))
+        .map(|trailer| {
+            let value = trailer.value.trim();
+            match value
+                .strip_suffix('>')
+                .and_then(|value| value.rsplit_once('<'))
+            {
+                Some((name, email)) => Person {
+                    name: name.trim().to_string(),
+                    email: email.trim().to_string(),
+                },
+                None => Person {
+                    name: value.to_string(),
+                    email: String::new(),
+                },
+            }
+        })
+        .filter(|person| !person.name.is_empty() || !person.email.is_empty())
+        .collect()
+}
+
+/// Sorts entries newest first. Dates fall back on the ancestry when they are
+/// equal, and the ancestry cannot be fooled by skewed clocks.
+fn sort_newest_first(entries: &mut [SourcedEntry], order: CommitOrder) {
+    match order {
+        CommitOrder::Topo => entries.sort_by_key(|entry| entry.key.rank),
+        CommitOrder::AuthorDate => {
+            entries.sort_by_key(|entry| (std::cmp::Reverse(entry.key.author_time), entry.key.rank))
+        }
+        CommitOrder::CommitterDate => entries
+            .sort_by_key(|entry| (std::cmp::Reverse(entry.key.committer_time), entry.key.rank)),
+    }
+}
+
+/// Turns entries sorted newest first into the requested direction.
+fn in_sort_order(entries: Vec<SourcedEntry>, sort_order: &SortOrder) -> Vec<LineEntry> {
+    let entries = entries.into_iter().map(|entry| entry.entry);
+    match sort_order {
+        SortOrder::Desc => entries.collect(),
+        SortOrder::Asc => entries.rev().collect(),
+    }
+}
+
+/// Pairs a line inside a hunk with the old lines while leaving blank lines on
+/// both sides out, so that blank lines added or removed in the same hunk do not
+/// shift the pairing.
+///
+/// Returns `None` when the line is not inside a hunk or is blank itself.
+fn pair_skipping_blank_lines(
+    hunks: &[HunkRange],
+    old_lines: Lines,
+    new_lines: Lines,
+    line_number: u32,
+) -> Option<LineOrigin> {
+    let is_blank =
+        |lines: Lines, line: u32| lines.text(line).is_some_and(|text| text.trim().is_empty());
+
+    let hunk = hunks.iter().find(|hunk| {
+        hunk.new_lines > 0
+            && hunk.new_start <= line_number
+            && line_number < hunk.new_start + hunk.new_lines
+    })?;
+    if is_blank(new_lines, line_number) {
+        return None;
+    }
+
+    let index = (hunk.new_start..line_number)
+        .filter(|&line| !is_blank(new_lines, line))
+        .count();
+    let old_line = (hunk.old_start..hunk.old_start + hunk.old_lines)
+        .filter(|&line| !is_blank(old_lines, line))
+        .nth(index);
+
+    Some(match old_line {
+        Some(old_line) => LineOrigin::Changed(old_line),
+        None => map_line_to_parent(hunks, line_number),
+    })
+}
+
+/// Turns every run of whitespace into a single space and drops trailing
+/// whitespace, like `git diff -b` compares lines.
+fn collapse_whitespace(line: &str) -> String {
+    let mut collapsed = String::with_capacity(line.len());
+    let mut in_whitespace = false;
+    for c in line.trim_end().chars() {
+        if c.is_whitespace() {
+            if !in_whitespace {
+                collapsed.push(' ');
+            }
+            in_whitespace = true;
+        } else {
+            collapsed.push(c);
+            in_whitespace = false;
+        }
+    }
+    collapsed
+}
+
+/// Where each line of some content starts, found in a single scan so that
+/// any line can then be read without going over the content again.
+#[derive(Debug, Clone, PartialEq)]
+struct LineIndex {
+    /// Offset of the start of each line, followed by the end of the last one
+    bounds: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(content: &[u8]) -> Self {
+        let mut bounds = vec![0];
+        bounds.extend(
+            content
+                .iter()
+                .enumerate()
+                .filter(|&(_, &byte)| byte == b'\n')
+                .map(|(offset, _)| offset + 1),
+        );
+        if !content.is_empty() && !content.ends_with(b"\n") {
+            bounds.push(content.len());
+        }
+        Self { bounds }
+    }
+
+    /// The lines of the content the index was made from.
+    fn lines<'a>(&'a self, content: &'a [u8]) -> Lines<'a> {
+        Lines {
+            content,
+            index: self,
+        }
+    }
+}
+
+/// Content along with the index of its lines.
+#[derive(Clone, Copy)]
+struct Lines<'a> {
+    content: &'a [u8],
+    index: &'a LineIndex,
+}
+
+impl<'a> Lines<'a> {
+    fn count(self) -> u32 {
+        (self.index.bounds.len() - 1) as u32
+    }
+
+    /// Returns a 1-based line without its line terminator.
+    fn get(self, line_number: u32) -> Option<&'a [u8]> {
+        let index = (line_number as usize).checked_sub(1)?;
+        let start = *self.index.bounds.get(index)?;
+        let end = *self.index.bounds.get(index + 1)?;
+        Some(trim_line_terminator(&self.content[start..end]))
+    }
+
+    fn text(self, line_number: u32) -> Option<String> {
+        self.get(line_number)
+            .map(|line| String::from_utf8_lossy(line).into_owned())
+    }
+}
+
+/// A blob along with the index of its lines.
+struct IndexedBlob<'repo> {
+    blob: git2::Blob<'repo>,
+    index: LineIndex,
+}
+
+impl<'repo> IndexedBlob<'repo> {
+    fn new(blob: git2::Blob<'repo>) -> Self {
+        let index = LineIndex::new(blob.content());
+        Self { blob, index }
+    }
+
+    fn lines(&self) -> Lines<'_> {
+        self.index.lines(self.blob.content())
+    }
+}
+
+/// Maps the text of each line of a blob to the first line with that text.
+fn all_lines(content: &[u8]) -> HashMap<Vec<u8>, u32> {
+    let mut lines = HashMap::new();
+    for (index, line) in content
+        .split(|&byte| byte == b'\n')
+        .take(count_lines(content) as usize)
+        .enumerate()
+    {
+        lines
+            .entry(trim_line_terminator(line).to_vec())
+            .or_insert(index as u32 + 1);
+    }
+    lines
+}
+
+fn trim_line_terminator(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// Whether a line is specific enough to be recognised when it reappears.
+///
+/// Blank lines and lone braces are re-created all the time, so matching them
+/// against earlier deletions would only produce noise.
+fn is_distinctive(content: &str) -> bool {
+    content.chars().any(char::is_alphanumeric)
+}
+
+/// Reads the revisions of an ignore-revs file, skipping comments and blank lines.
+fn parse_ignore_revs(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Expands a leading `~/` of a configured path to the home directory, like
+/// git does for path-valued settings.
+fn expand_home(value: &str, home: Option<&Path>) -> PathBuf {
+    match (value.strip_prefix("~/"), home) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ if value == "~" => home.map_or_else(|| PathBuf::from(value), Path::to_path_buf),
+        _ => PathBuf::from(value),
+    }
+}
+
+/// Parses a full hexadecimal object name, rejecting abbreviations.
+fn parse_full_oid(rev: &str) -> Option<git2::Oid> {
+    if rev.len() == 40 && rev.chars().all(|c| c.is_ascii_hexdigit()) {
+        git2::Oid::from_str(rev).ok()
+    } else {
+        None
+    }
+}
+
+/// The git directory shared by all the worktrees of a repository, which is
+/// not the one of a linked worktree.
+fn common_dir(repository: &Repository) -> PathBuf {
+    let git_dir = repository.path();
+    match std::fs::read_to_string(git_dir.join("commondir")) {
+        Ok(common_dir) => normalize_path(&git_dir.join(common_dir.trim())),
+        Err(_) => git_dir.to_path_buf(),
+    }
+}
+
+/// Resolves `.` and `..` components without touching the filesystem.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Formats a relative path the way git does, with `/` separators.
+fn path_to_slash(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn count_lines(content: &[u8]) -> u32 {
+    let newlines = content.iter().filter(|&&byte| byte == b'\n').count();
+    let unterminated = !content.is_empty() && !content.ends_with(b"\n");
+    (newlines + usize::from(unterminated)) as u32
+}
+
+impl LineHistoryProvider for GitAdapter {
+    fn get_line_history(
+        &self,
+        file_path: &str,
+        line_number: u32,
+        options: &HistoryOptions,
+    ) -> Result<LineHistory> {
+        let selector = LineSelector::Range {
+            start: RangeBound::Line(line_number),
+            end: None,
+        };
+        self.get_line_range_history(file_path, &selector, options)
+    }
+
+    fn get_line_range_history(
+        &self,
+        file_path: &str,
+        selector: &LineSelector,
+        options: &HistoryOptions,
+    ) -> Result<LineHistory> {
+        let file_path = &self.resolve_path(file_path)?;
+
+        // Use full history extraction for multiple commits
+        let (start, end, entries) = self.extract_full_line_history(file_path, selector, options)?;
+
+        let mut history = LineHistory::for_range(file_path.to_string(), start, end);
+        for entry in entries {
+            history.add_entry(entry);
+        }
+
+        Ok(history)
+    }
+
+    fn get_file_history(&self, file_path: &str, options: &HistoryOptions) -> Result<FileHistory> {
+        let file_path = &self.resolve_path(file_path)?;
+        self.extract_file_history(file_path, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn setup_test_repo() -> Result<TempDir> {
+        let temp_dir = TempDir::new()?;
+        let repo_path = temp_dir.path();
+
+        // Initialize git repository
+        let repo = Repository::init(repo_path)?;
+
+        // Configure git user
+        let mut config = repo.config()?;
+        config.set_str("user.name", "Test User")?;
+        config.set_str("user.email", "test@example.com")?;
+
+        // Create initial file
+        let file_path = repo_path.join("test.txt");
+        fs::write(&file_path, "line 1\nline 2\nline 3\n")?;
+
+        // Add and commit
+        let mut index = repo.index()?;
+        index.add_path(Path::new("test.txt"))?;
+        index.write()?;
+
+        let tree_id = index.write_tree()?;
+        let tree = repo.find_tree(tree_id)?;
+        let signature = git2::Signature::now("Test User", "test@example.com")?;
+
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Initial commit",
+            &tree,
+            &[],
+        )?;
+
+        Ok(temp_dir)
+    }
+
+    fn setup_test_repo_with_multiple_commits() -> Result<TempDir> {
+        let temp_dir = TempDir::new()?;
+        let repo_path = temp_dir.path();
 
         // Initialize git repository
         let repo = Repository::init(repo_path)?;
 
-        // Configure git user
-        let mut config = repo.config()?;
-        config.set_str("user.name", "Test User")?;
-        config.set_str("user.email", "test@example.com")?;
+        // Configure git user
+        let mut config = repo.config()?;
+        config.set_str("user.name", "Test User")?;
+        config.set_str("user.email", "test@example.com")?;
+
+        // First commit - create file
+        let file_path = repo_path.join("test.txt");
+        fs::write(&file_path, "original line 1\nline 2\nline 3\n")?;
+
+        let mut index = repo.index()?;
+        index.add_path(Path::new("test.txt"))?;
+        index.write()?;
+
+        let tree_id = index.write_tree()?;
+        let tree = repo.find_tree(tree_id)?;
+        let signature1 =
+            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(1000, 0))?;
+
+        let initial_commit = repo.commit(
+            Some("HEAD"),
+            &signature1,
+            &signature1,
+            "Initial commit",
+            &tree,
+            &[],
+        )?;
+
+        // Second commit - modify line 1
+        fs::write(
+            &file_path,
+            "modified line 1 - first change\nline 2\nline 3\n",
+        )?;
+
+        let mut index = repo.index()?;
+        index.add_path(Path::new("test.txt"))?;
+        index.write()?;
+
+        let tree_id = index.write_tree()?;
+        let tree = repo.find_tree(tree_id)?;
+        let parent_commit = repo.find_commit(initial_commit)?;
+        let signature2 =
+            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(2000, 0))?;
+
+        let second_commit = repo.commit(
+            Some("HEAD"),
+            &signature2,
+            &signature2,
+            "Update line 1 - first change",
+            &tree,
+            &[&parent_commit],
+        )?;
+
+        // Third commit - modify line 1 again
+        fs::write(
+            &file_path,
+            "modified line 1 - second change\nline 2\nline 3\n",
+        )?;
+
+        let mut index = repo.index()?;
+        index.add_path(Path::new("test.txt"))?;
+        index.write()?;
+
+        let tree_id = index.write_tree()?;
+        let tree = repo.find_tree(tree_id)?;
+        let parent_commit = repo.find_commit(second_commit)?;
+        let signature3 =
+            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(3000, 0))?;
+
+        repo.commit(
+            Some("HEAD"),
+            &signature3,
+            &signature3,
+            "Update line 1 - second change",
+            &tree,
+            &[&parent_commit],
+        )?;
+
+        Ok(temp_dir)
+    }
+
+    fn commit_file(
+        repo: &Repository,
+        file_name: &str,
+        content: &str,
+        message: &str,
+        time: i64,
+    ) -> Result<git2::Oid> {
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        let file_path = workdir.join(file_name);
+        if let Some(parent_dir) = file_path.parent() {
+            fs::create_dir_all(parent_dir)?;
+        }
+        fs::write(&file_path, content)?;
+
+        let mut index = repo.index()?;
+        index.add_path(Path::new(file_name))?;
+        index.write()?;
+
+        let tree_id = index.write_tree()?;
+        let tree = repo.find_tree(tree_id)?;
+        let signature =
+            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(time, 0))?;
+        let parents = match repo.head() {
+            Ok(head) => vec![head.peel_to_commit()?],
+            Err(_) => Vec::new(),
+        };
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+
+        Ok(repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parent_refs,
+        )?)
+    }
+
+    #[test]
+    fn test_git_adapter_creation() {
+        let temp_dir = setup_test_repo().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        // Test that adapter was created successfully
+        assert!(adapter.repository.path().exists());
+    }
+
+    #[test]
+    fn test_git_adapter_get_line_history() {
+        let temp_dir = setup_test_repo().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        let history = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+
+        assert_eq!(history.file_path, "test.txt");
+        assert_eq!(history.line_number, 1);
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].author, "Test User");
+        assert_eq!(history.entries[0].subject, "Initial commit");
+        assert_eq!(history.entries[0].content, "line 1");
+        assert_eq!(history.entries[0].file_path, "test.txt");
+    }
+
+    #[test]
+    fn test_git_adapter_nonexistent_file() {
+        let temp_dir = setup_test_repo().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        let result = adapter.get_line_history("nonexistent.txt", 1, &HistoryOptions::default());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_git_adapter_multiple_commit_history() {
+        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        let history = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+
+        assert_eq!(history.file_path, "test.txt");
+        assert_eq!(history.line_number, 1);
+
+        // Debug output
+        println!("Found {} entries:", history.entries.len());
+        for (i, entry) in history.entries.iter().enumerate() {
+            println!("  {}: {} - {}", i, &entry.commit_hash[..8], entry.subject);
+        }
+
+        // This should fail initially - we expect 3 commits but only get 1
+        assert_eq!(history.entries.len(), 3);
+
+        // Verify the entries are in chronological order (oldest first)
+        assert_eq!(history.entries[0].subject, "Initial commit");
+        assert_eq!(history.entries[1].subject, "Update line 1 - first change");
+        assert_eq!(history.entries[2].subject, "Update line 1 - second change");
+
+        // Each entry holds the line as it was in that commit
+        assert_eq!(history.entries[0].content, "original line 1");
+        assert_eq!(history.entries[1].content, "modified line 1 - first change");
+        assert_eq!(
+            history.entries[2].content,
+            "modified line 1 - second change"
+        );
+    }
+
+    #[test]
+    fn test_git_adapter_sort_order() {
+        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        // Test ascending order (oldest first)
+        let history_asc = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+
+        // Test descending order (newest first)
+        let history_desc = adapter
+            .get_line_history(
+                "test.txt",
+                1,
+                &HistoryOptions {
+                    sort_order: SortOrder::Desc,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        // Both should have the same number of entries
+        assert_eq!(history_asc.entries.len(), history_desc.entries.len());
+        assert_eq!(history_asc.entries.len(), 3);
+
+        // Ascending order: oldest first
+        assert_eq!(history_asc.entries[0].subject, "Initial commit");
+        assert_eq!(
+            history_asc.entries[1].subject,
+            "Update line 1 - first change"
+        );
+        assert_eq!(
+            history_asc.entries[2].subject,
+            "Update line 1 - second change"
+        );
+
+        // Descending order: newest first
+        assert_eq!(
+            history_desc.entries[0].subject,
+            "Update line 1 - second change"
+        );
+        assert_eq!(
+            history_desc.entries[1].subject,
+            "Update line 1 - first change"
+        );
+        assert_eq!(history_desc.entries[2].subject, "Initial commit");
+
+        // Verify descending order has later timestamps first
+        assert!(history_desc.entries[0].timestamp >= history_desc.entries[1].timestamp);
+        assert!(history_desc.entries[1].timestamp >= history_desc.entries[2].timestamp);
+    }
+
+    #[test]
+    fn test_git_adapter_ignore_single_revision() {
+        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        // First get all commits to find one to ignore
+        let history_all = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+
+        assert_eq!(history_all.entries.len(), 3);
+
+        // Get the hash of the second commit to ignore
+        let ignore_hash = &history_all.entries[1].commit_hash[..8]; // Use abbreviated hash
+        let ignore_revs = vec![ignore_hash.to_string()];
+
+        // Test with ignored revision
+        let history_filtered = adapter
+            .get_line_history(
+                "test.txt",
+                1,
+                &HistoryOptions {
+                    ignore_revs: ignore_revs.clone(),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        // Should have one less commit
+        assert_eq!(history_filtered.entries.len(), 2);
+
+        // Verify the ignored commit is not present
+        for entry in &history_filtered.entries {
+            assert!(!entry.commit_hash.starts_with(ignore_hash));
+        }
+    }
+
+    #[test]
+    fn test_git_adapter_ignore_multiple_revisions() {
+        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        // First get all commits
+        let history_all = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+
+        assert_eq!(history_all.entries.len(), 3);
+
+        // Ignore first and third commits
+        let ignore_revs = vec![
+            history_all.entries[0].commit_hash[..8].to_string(),
+            history_all.entries[2].commit_hash[..8].to_string(),
+        ];
+
+        let history_filtered = adapter
+            .get_line_history(
+                "test.txt",
+                1,
+                &HistoryOptions {
+                    ignore_revs: ignore_revs.clone(),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        // Should have only one commit remaining
+        assert_eq!(history_filtered.entries.len(), 1);
+        assert_eq!(
+            history_filtered.entries[0].subject,
+            "Update line 1 - first change"
+        );
+    }
+
+    #[test]
+    fn test_git_adapter_ignore_nonexistent_revision() {
+        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        let options = HistoryOptions {
+            ignore_revs: vec!["fakehash123".to_string()],
+            ..Default::default()
+        };
+        let error = adapter
+            .get_line_history("test.txt", 1, &options)
+            .unwrap_err();
+        assert!(error.to_string().contains("Unknown revision 'fakehash123'"));
+
+        // Too short to name a single commit
+        let options = HistoryOptions {
+            ignore_revs: vec!["a".to_string()],
+            ..Default::default()
+        };
+        assert!(adapter.get_line_history("test.txt", 1, &options).is_err());
+    }
+
+    #[test]
+    fn test_git_adapter_ignore_symbolic_revisions() {
+        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
+        let repo = Repository::open(temp_dir.path()).unwrap();
+        let first = repo.revparse_single("HEAD~2").unwrap();
+        repo.tag_lightweight("v1.0", &first, false).unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        let options = HistoryOptions {
+            ignore_revs: vec!["HEAD~1".to_string(), "v1.0".to_string()],
+            ..Default::default()
+        };
+        let history = adapter.get_line_history("test.txt", 1, &options).unwrap();
+
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].subject, "Update line 1 - second change");
+    }
+
+    #[test]
+    fn test_git_adapter_passes_through_ignored_commit() {
+        let temp_dir = setup_test_repo().unwrap();
+        let repo = Repository::open(temp_dir.path()).unwrap();
+        commit_file(&repo, "split.rs", "let x = 1; let y = 2;\n", "Add x", 1000).unwrap();
+        let reformat = commit_file(
+            &repo,
+            "split.rs",
+            "let x = 1;\nlet y = 2;\n",
+            "Reformat",
+            2000,
+        )
+        .unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        let history = adapter
+            .get_line_history("split.rs", 2, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].subject, "Reformat");
+
+        // The line is attributed to the change before the ignored one
+        let options = HistoryOptions {
+            ignore_revs: vec![reformat.to_string()],
+            ..Default::default()
+        };
+        let history = adapter.get_line_history("split.rs", 2, &options).unwrap();
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].subject, "Add x");
+        assert_eq!(history.entries[0].change_type, ChangeType::Created);
+    }
+
+    #[test]
+    fn test_git_adapter_limit() {
+        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        let options = HistoryOptions {
+            limit: Some(2),
+            ..Default::default()
+        };
+        let history = adapter.get_line_history("test.txt", 1, &options).unwrap();
+
+        // The most recent changes are kept
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(history.entries[0].subject, "Update line 1 - first change");
+        assert_eq!(history.entries[1].subject, "Update line 1 - second change");
+
+        let options = HistoryOptions {
+            limit: Some(0),
+            ..Default::default()
+        };
+        let history = adapter.get_line_history("test.txt", 1, &options).unwrap();
+        assert!(history.entries.is_empty());
+    }
+
+    #[test]
+    fn test_git_adapter_history_from_revision() {
+        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
+        let repo = Repository::open(temp_dir.path()).unwrap();
+        commit_file(
+            &repo,
+            "test.txt",
+            "inserted\nmodified line 1 - second change\nline 2\nline 3\n",
+            "Insert a line on top",
+            4000,
+        )
+        .unwrap();
+
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        // Line 1 in HEAD~1 is line 2 in HEAD
+        let options = HistoryOptions {
+            rev: Some("HEAD~1".to_string()),
+            ..Default::default()
+        };
+        let history = adapter.get_line_history("test.txt", 1, &options).unwrap();
+        assert_eq!(history.entries.len(), 3);
+        assert_eq!(
+            history.entries[2].content,
+            "modified line 1 - second change"
+        );
+
+        let options = HistoryOptions {
+            rev: Some("HEAD~2".to_string()),
+            ..Default::default()
+        };
+        let history = adapter.get_line_history("test.txt", 1, &options).unwrap();
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(history.entries[1].subject, "Update line 1 - first change");
+    }
+
+    #[test]
+    fn test_git_adapter_history_within_range() {
+        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        let options = HistoryOptions {
+            rev: Some("HEAD~2..HEAD".to_string()),
+            ..Default::default()
+        };
+        let history = adapter.get_line_history("test.txt", 1, &options).unwrap();
+
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(history.entries[0].subject, "Update line 1 - first change");
+        assert_eq!(history.entries[1].subject, "Update line 1 - second change");
+    }
+
+    #[test]
+    fn test_git_adapter_invalid_revision() {
+        let temp_dir = setup_test_repo().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        for rev in ["no-such-branch", "HEAD~5", "HEAD...HEAD"] {
+            let options = HistoryOptions {
+                rev: Some(rev.to_string()),
+                ..Default::default()
+            };
+            assert!(adapter.get_line_history("test.txt", 1, &options).is_err());
+        }
+    }
+
+    #[test]
+    fn test_git_adapter_reports_uncommitted_changes() {
+        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
+        let repo = Repository::open(temp_dir.path()).unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        fs::write(
+            temp_dir.path().join("test.txt"),
+            "modified line 1 - local change\nline 2\nline 3\n",
+        )
+        .unwrap();
+        let history = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.entries.len(), 4);
+        let local = &history.entries[3];
+        assert_eq!(local.author, "Not Committed Yet");
+        assert_eq!(local.commit_hash, git2::Oid::zero().to_string());
+        assert_eq!(local.change_type, ChangeType::Modified);
+        assert_eq!(local.content, "modified line 1 - local change");
+
+        // Staged, then shifted down by a line added on disk only
+        stage_file(
+            &repo,
+            "test.txt",
+            "modified line 1 - staged change\nline 2\nline 3\n",
+        );
+        fs::write(
+            temp_dir.path().join("test.txt"),
+            "new first line\nmodified line 1 - staged change\nline 2\nline 3\n",
+        )
+        .unwrap();
+        let history = adapter
+            .get_line_history("test.txt", 2, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.entries.len(), 4);
+        assert_eq!(history.entries[3].author, "Staged");
+        assert_eq!(
+            history.entries[3].content,
+            "modified line 1 - staged change"
+        );
+
+        let history = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].author, "Not Committed Yet");
+        assert_eq!(history.entries[0].change_type, ChangeType::Created);
+
+        // Changed again on disk, so staged and uncommitted entries both count
+        // towards the limit
+        fs::write(
+            temp_dir.path().join("test.txt"),
+            "new first line\nmodified line 1 - local change\nline 2\nline 3\n",
+        )
+        .unwrap();
+        let options = HistoryOptions {
+            limit: Some(1),
+            ..Default::default()
+        };
+        let history = adapter.get_line_history("test.txt", 2, &options).unwrap();
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].author, "Not Committed Yet");
+
+        // Only committed history is looked at for a revision
+        let options = HistoryOptions {
+            rev: Some("HEAD".to_string()),
+            ..Default::default()
+        };
+        let history = adapter.get_line_history("test.txt", 1, &options).unwrap();
+        assert_eq!(history.entries.len(), 3);
+    }
+
+    #[test]
+    fn test_git_adapter_uncommitted_files() {
+        let temp_dir = setup_test_repo().unwrap();
+        let repo = Repository::open(temp_dir.path()).unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        stage_file(&repo, "staged.txt", "staged line\n");
+        let history = adapter
+            .get_line_history("staged.txt", 1, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].author, "Staged");
+        assert_eq!(history.entries[0].change_type, ChangeType::Created);
+
+        // Line numbers cannot refer to a file deleted from disk
+        fs::remove_file(temp_dir.path().join("staged.txt")).unwrap();
+        let error = adapter
+            .get_line_history("staged.txt", 1, &HistoryOptions::default())
+            .unwrap_err();
+        assert!(error.to_string().contains("deleted in the working tree"));
+
+        // Untracked files have no history at all
+        fs::write(temp_dir.path().join("untracked.txt"), "line\n").unwrap();
+        assert!(
+            adapter
+                .get_line_history("untracked.txt", 1, &HistoryOptions::default())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_git_adapter_opened_from_subdirectory() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_file(&repo, "src/lib.rs", "pub fn lib() {}\n", "Add lib", 1000).unwrap();
+        commit_file(&repo, "README.md", "# Readme\n", "Add readme", 2000).unwrap();
+
+        let src_dir = temp_dir.path().join("src");
+        let adapter = GitAdapter::new(&src_dir).unwrap();
+
+        for path in ["lib.rs", "./lib.rs", "../src/lib.rs"] {
+            let history = adapter
+                .get_line_history(path, 1, &HistoryOptions::default())
+                .unwrap();
+            assert_eq!(history.file_path, "src/lib.rs");
+            assert_eq!(history.entries.len(), 1);
+        }
+
+        let history = adapter
+            .get_line_history("../README.md", 1, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.file_path, "README.md");
+
+        let absolute = temp_dir.path().join("src").join("lib.rs");
+        let history = adapter
+            .get_line_history(absolute.to_str().unwrap(), 1, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.file_path, "src/lib.rs");
+
+        let outside = TempDir::new().unwrap();
+        let outside_file = outside.path().join("other.rs");
+        assert!(
+            adapter
+                .get_line_history(
+                    outside_file.to_str().unwrap(),
+                    1,
+                    &HistoryOptions::default()
+                )
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_git_adapter_opened_from_linked_worktree() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_file(&repo, "src/lib.rs", "pub fn lib() {}\n", "Add lib", 1000).unwrap();
+        let worktrees_dir = TempDir::new().unwrap();
+        let worktree_path = worktrees_dir.path().join("linked");
+        repo.worktree("linked", &worktree_path, None).unwrap();
+
+        // Uncommitted changes are those of the worktree, not the main one
+        fs::write(
+            worktree_path.join("src").join("lib.rs"),
+            "pub fn linked() {}\n",
+        )
+        .unwrap();
+        let adapter = GitAdapter::new(&worktree_path.join("src")).unwrap();
+        let history = adapter
+            .get_line_history("lib.rs", 1, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.file_path, "src/lib.rs");
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(history.entries[0].subject, "Add lib");
+        assert_eq!(history.entries[1].author, "Not Committed Yet");
+        assert_eq!(history.entries[1].content, "pub fn linked() {}");
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(
+            normalize_path(Path::new("/repo/src/./../lib.rs")),
+            PathBuf::from("/repo/lib.rs")
+        );
+        assert_eq!(
+            path_to_slash(&normalize_path(Path::new("./a/b/../c.rs"))),
+            "a/c.rs"
+        );
+    }
+
+    #[test]
+    fn test_git_adapter_ignore_revs_file() {
+        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        let history_all = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history_all.entries.len(), 3);
+
+        let ignored_hash = history_all.entries[1].commit_hash.clone();
+        fs::write(
+            temp_dir.path().join(".git-blame-ignore-revs"),
+            format!("# Formatting\n\n{} # reformat\n", ignored_hash),
+        )
+        .unwrap();
+
+        // Only used once configured, like git blame does
+        let history = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.entries.len(), 3);
+
+        Repository::open(temp_dir.path())
+            .unwrap()
+            .config()
+            .unwrap()
+            .set_str("blame.ignoreRevsFile", ".git-blame-ignore-revs")
+            .unwrap();
+        let history = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.entries.len(), 2);
+        assert!(
+            history
+                .entries
+                .iter()
+                .all(|entry| entry.commit_hash != ignored_hash)
+        );
+
+        let options = HistoryOptions {
+            no_ignore_revs_file: true,
+            ..Default::default()
+        };
+        let history = adapter.get_line_history("test.txt", 1, &options).unwrap();
+        assert_eq!(history.entries.len(), 3);
+    }
+
+    #[test]
+    fn test_git_adapter_configured_and_explicit_ignore_revs_files() {
+        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        let history_all = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+
+        fs::write(
+            temp_dir.path().join("formatting-revs"),
+            format!("{}\n", history_all.entries[2].commit_hash),
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("other-revs"),
+            format!("{}\n", history_all.entries[0].commit_hash),
+        )
+        .unwrap();
+
+        let repo = Repository::open(temp_dir.path()).unwrap();
+        repo.config()
+            .unwrap()
+            .set_str("blame.ignoreRevsFile", "formatting-revs")
+            .unwrap();
+
+        let history = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(history.entries[1].subject, "Update line 1 - first change");
+
+        let options = HistoryOptions {
+            ignore_revs_files: vec!["other-revs".to_string()],
+            ..Default::default()
+        };
+        let history = adapter.get_line_history("test.txt", 1, &options).unwrap();
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].subject, "Update line 1 - first change");
+
+        let options = HistoryOptions {
+            ignore_revs_files: vec!["missing-revs".to_string()],
+            ..Default::default()
+        };
+        assert!(adapter.get_line_history("test.txt", 1, &options).is_err());
+
+        // Files only take full object names
+        fs::write(temp_dir.path().join("short-revs"), "HEAD~1\n").unwrap();
+        let options = HistoryOptions {
+            ignore_revs_files: vec!["short-revs".to_string()],
+            ..Default::default()
+        };
+        let error = adapter
+            .get_line_history("test.txt", 1, &options)
+            .unwrap_err();
+        assert!(error.to_string().contains("Invalid object name 'HEAD~1'"));
+    }
+
+    #[test]
+    fn test_parse_ignore_revs() {
+        let content = "# Formatting commits\n\nabc123\n  def456  # cargo fmt\n#ghi789\n";
+        assert_eq!(parse_ignore_revs(content), vec!["abc123", "def456"]);
+    }
+
+    #[test]
+    fn test_expand_home() {
+        let home = Path::new("/home/user");
+        assert_eq!(
+            expand_home("~/revs", Some(home)),
+            PathBuf::from("/home/user/revs")
+        );
+        assert_eq!(expand_home("~", Some(home)), PathBuf::from("/home/user"));
+        assert_eq!(expand_home("revs", Some(home)), PathBuf::from("revs"));
+        assert_eq!(
+            expand_home("~other/revs", Some(home)),
+            PathBuf::from("~other/revs")
+        );
+        assert_eq!(expand_home("~/revs", None), PathBuf::from("~/revs"));
+    }
+
+    #[test]
+    fn test_git_adapter_parse_date_iso8601() {
+        let now = Utc::now().fixed_offset();
+
+        // Test parsing various ISO 8601 formats
+        let iso_date = "2023-01-01T00:00:00Z";
+        let parsed = date::parse_date(iso_date, now).unwrap();
+        assert_eq!(parsed.timestamp(), 1672531200); // 2023-01-01 UTC
+
+        let iso_with_tz = "2023-01-01T09:00:00+09:00";
+        let parsed_tz = date::parse_date(iso_with_tz, now).unwrap();
+        assert_eq!(parsed_tz.timestamp(), 1672531200); // Same UTC time
+    }
+
+    #[test]
+    fn test_git_adapter_parse_date_simple_formats() {
+        let now = Utc::now().fixed_offset();
+
+        // Test simple date format
+        let simple_date = "2023-01-01";
+        let parsed = date::parse_date(simple_date, now).unwrap();
+        assert_eq!(parsed.format("%Y-%m-%d").to_string(), "2023-01-01");
+
+        // Test datetime format
+        let datetime = "2023-01-01 12:00:00";
+        let parsed_dt = date::parse_date(datetime, now).unwrap();
+        assert_eq!(parsed_dt.format("%H").to_string(), "12");
+    }
+
+    #[test]
+    fn test_git_adapter_parse_date_formats() {
+        let now = DateTime::parse_from_rfc3339("2023-06-15T12:00:00+09:00").unwrap();
+
+        // Dates without a timezone are in the timezone of now
+        let iso_local = "2023-01-01T09:00:00";
+        let parsed = date::parse_date(iso_local, now).unwrap();
+        assert_eq!(parsed.timestamp(), 1672531200);
+
+        // Test error case
+        let invalid_date = "not-a-date";
+        let result = date::parse_date(invalid_date, now);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_git_adapter_filter_with_timezone() {
+        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+        let all = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+        let first = all.entries[0].timestamp.to_utc();
+
+        // Read in UTC, the bare date is the time of the first commit; read
+        // two hours east of UTC, it is two hours before it
+        let bare_date = first.format("%Y-%m-%d %H:%M:%S").to_string();
+        let options = |timezone: &str| HistoryOptions {
+            until: Some(bare_date.clone()),
+            timezone: Some(timezone.to_string()),
+            ..Default::default()
+        };
+        let history = adapter
+            .get_line_history("test.txt", 1, &options("UTC"))
+            .unwrap();
+        assert_eq!(history.entries.len(), 1);
+        let history = adapter
+            .get_line_history("test.txt", 1, &options("+0200"))
+            .unwrap();
+        assert!(history.entries.is_empty());
+
+        let error = adapter
+            .get_line_history("test.txt", 1, &options("Mars"))
+            .unwrap_err();
+        assert!(error.to_string().contains("Invalid timezone 'Mars'"));
+    }
+
+    #[test]
+    fn test_git_adapter_filter_by_since_date() {
+        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        // Get all commits first
+        let history_all = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+
+        assert_eq!(history_all.entries.len(), 3);
+
+        // Filter to only show commits from a specific date onwards
+        // Use a timestamp between the first and second commit
+        let since_date = "1970-01-01T00:25:00Z"; // 1500 seconds epoch
+        let history_filtered = adapter
+            .get_line_history(
+                "test.txt",
+                1,
+                &HistoryOptions {
+                    since: Some(since_date.to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        // Should have fewer commits (only those after the since date)
+        assert!(history_filtered.entries.len() <= history_all.entries.len());
+        assert!(history_filtered.entries.len() >= 2); // Should have at least 2 commits
+    }
+
+    #[test]
+    fn test_git_adapter_filter_by_until_date() {
+        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        // Get all commits first
+        let history_all = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+
+        assert_eq!(history_all.entries.len(), 3);
+
+        // Filter to only show commits up to a specific date
+        // Use a timestamp between the second and third commit
+        let until_date = "1970-01-01T00:35:00Z"; // 2100 seconds epoch
+        let history_filtered = adapter
+            .get_line_history(
+                "test.txt",
+                1,
+                &HistoryOptions {
+                    until: Some(until_date.to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        // Should have fewer commits (only those before the until date)
+        assert!(history_filtered.entries.len() <= history_all.entries.len());
+        assert!(!history_filtered.entries.is_empty()); // Should have at least 1 commit
+    }
 
-        // First commit - create file
-        let file_path = repo_path.join("test.txt");
-        fs::write(&file_path, "original line 1\nline 2\nline 3\n")?;
+    #[test]
+    fn test_git_adapter_filter_by_date_range() {
+        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
 
-        let mut index = repo.index()?;
-        index.add_path(Path::new("test.txt"))?;
-        index.write()?;
+        // Get all commits first
+        let history_all = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
 
-        let tree_id = index.write_tree()?;
-        let tree = repo.find_tree(tree_id)?;
-        let signature1 =
-            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(1000, 0))?;
+        assert_eq!(history_all.entries.len(), 3);
 
-        let initial_commit = repo.commit(
-            Some("HEAD"),
-            &signature1,
-            &signature1,
-            "Initial commit",
-            &tree,
-            &[],
-        )?;
+        // Filter to a specific date range that should include only the middle commit
+        let since_date = "1970-01-01T00:25:00Z"; // 1500 seconds
+        let until_date = "1970-01-01T00:35:00Z"; // 2100 seconds
+        let history_filtered = adapter
+            .get_line_history(
+                "test.txt",
+                1,
+                &HistoryOptions {
+                    since: Some(since_date.to_string()),
+                    until: Some(until_date.to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
 
-        // Second commit - modify line 1
+        // Should have exactly 1 commit (the middle one)
+        assert_eq!(history_filtered.entries.len(), 1);
+        assert_eq!(
+            history_filtered.entries[0].subject,
+            "Update line 1 - first change"
+        );
+    }
+
+    #[test]
+    fn test_git_adapter_invalid_date_fails_before_walking() {
+        let temp_dir = setup_test_repo().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        // Line 1 was never committed, so no commit is looked at
         fs::write(
-            &file_path,
-            "modified line 1 - first change\nline 2\nline 3\n",
+            temp_dir.path().join("test.txt"),
+            "new\nline 1\nline 2\nline 3\n",
+        )
+        .unwrap();
+        let options = HistoryOptions {
+            since: Some("not a date".to_string()),
+            ..Default::default()
+        };
+        let error = adapter
+            .get_line_history("test.txt", 1, &options)
+            .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("Unable to parse date 'not a date'")
+        );
+    }
+
+    #[test]
+    fn test_git_adapter_only_diffs_commits_changing_the_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_file(&repo, "test.txt", "a\nb\n", "Add test", 1000).unwrap();
+        for time in 2000..2005 {
+            commit_file(&repo, "other.txt", &time.to_string(), "Edit other", time).unwrap();
+        }
+        commit_file(&repo, "test.txt", "a\nb changed\n", "Change b", 3000).unwrap();
+        for time in 4000..4005 {
+            commit_file(&repo, "other.txt", &time.to_string(), "Edit other", time).unwrap();
+        }
+
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+        let history = adapter
+            .get_line_history("test.txt", 2, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.entries.len(), 2);
+
+        // Commits that leave the blob of the file as it is are never diffed
+        assert_eq!(adapter.cache().stats().unwrap().diffs, 1);
+    }
+
+    #[test]
+    fn test_git_adapter_stops_once_line_origin_is_found() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        for time in 1000..1010 {
+            commit_file(&repo, "test.txt", &format!("{}\n", time), "Count", time).unwrap();
+        }
+        commit_file(&repo, "test.txt", "1010\n}\n", "Close", 1010).unwrap();
+
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+        let history = adapter
+            .get_line_history("test.txt", 2, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.entries.len(), 1);
+
+        // Only the commit that created the line is diffed
+        assert_eq!(adapter.cache().stats().unwrap().diffs, 1);
+    }
+
+    #[test]
+    fn test_git_adapter_bounds_deleted_line_search() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let commits = i64::from(DELETED_LINE_SEARCH_DEPTH) + 10;
+        for time in 0..commits {
+            commit_file(&repo, "test.txt", &format!("{}\n", time), "Count", time).unwrap();
+        }
+        commit_file(&repo, "test.txt", "0\nflag = true\n", "Add flag", commits).unwrap();
+
+        // The earlier deletion of the line is only looked for in the
+        // commits closest to its creation
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+        let history = adapter
+            .get_line_history("test.txt", 2, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(
+            adapter.cache().stats().unwrap().diffs,
+            DELETED_LINE_SEARCH_DEPTH as usize + 1
+        );
+
+        // Deleted lines are followed through the whole history
+        adapter.cache().clear().unwrap();
+        let adapter = adapter.with_follow_deleted(true);
+        adapter
+            .get_line_history("test.txt", 2, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(adapter.cache().stats().unwrap().diffs, commits as usize);
+    }
+
+    #[test]
+    fn test_git_adapter_skips_commits_not_touching_line() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_file(&repo, "test.txt", "a\nb\nc\n", "Initial commit", 1000).unwrap();
+        commit_file(
+            &repo,
+            "test.txt",
+            "a\nb\nc changed\n",
+            "Change line 3",
+            2000,
+        )
+        .unwrap();
+
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        let history_line1 = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history_line1.entries.len(), 1);
+        assert_eq!(history_line1.entries[0].subject, "Initial commit");
+        assert_eq!(history_line1.entries[0].change_type, ChangeType::Created);
+
+        let history_line3 = adapter
+            .get_line_history("test.txt", 3, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history_line3.entries.len(), 2);
+        assert_eq!(history_line3.entries[1].subject, "Change line 3");
+        assert_eq!(history_line3.entries[1].change_type, ChangeType::Modified);
+    }
+
+    #[test]
+    fn test_git_adapter_follows_shifted_line() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_file(&repo, "test.txt", "a\nb\n", "Initial commit", 1000).unwrap();
+        commit_file(
+            &repo,
+            "test.txt",
+            "new 1\nnew 2\na\nb\n",
+            "Insert lines",
+            2000,
+        )
+        .unwrap();
+        commit_file(
+            &repo,
+            "test.txt",
+            "new 1\nnew 2\na changed\nb\n",
+            "Change a",
+            3000,
+        )
+        .unwrap();
+
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        let history = adapter
+            .get_line_history("test.txt", 3, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(history.entries[0].subject, "Initial commit");
+        assert_eq!(history.entries[0].change_type, ChangeType::Created);
+        assert_eq!(history.entries[1].subject, "Change a");
+        assert_eq!(history.entries[1].change_type, ChangeType::Modified);
+        // Each entry has the line where it was in that commit
+        assert_eq!(history.entries[0].line_number, 1);
+        assert_eq!(history.entries[1].line_number, 3);
+
+        let inserted = adapter
+            .get_line_history("test.txt", 2, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(inserted.entries.len(), 1);
+        assert_eq!(inserted.entries[0].subject, "Insert lines");
+        assert_eq!(inserted.entries[0].change_type, ChangeType::Created);
+    }
+
+    #[test]
+    fn test_git_adapter_line_out_of_range() {
+        let temp_dir = setup_test_repo().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+
+        assert!(
+            adapter
+                .get_line_history("test.txt", 4, &HistoryOptions::default())
+                .is_err()
+        );
+        assert!(
+            adapter
+                .get_line_history("test.txt", 0, &HistoryOptions::default())
+                .is_err()
+        );
+    }
+
+    fn setup_test_repo_with_recreated_line() -> Result<TempDir> {
+        let temp_dir = TempDir::new()?;
+        let repo = Repository::init(temp_dir.path())?;
+        commit_file(&repo, "config.txt", "a\nflag = true\nb\n", "Add flag", 1000)?;
+        commit_file(
+            &repo,
+            "config.txt",
+            "a\nflag = false\nb\n",
+            "Disable flag",
+            2000,
+        )?;
+        commit_file(&repo, "config.txt", "a\nb\n", "Remove flag", 3000)?;
+        commit_file(
+            &repo,
+            "config.txt",
+            "a\nb\nflag = false\n",
+            "Restore flag",
+            4000,
         )?;
+        Ok(temp_dir)
+    }
 
-        let mut index = repo.index()?;
-        index.add_path(Path::new("test.txt"))?;
-        index.write()?;
+    #[test]
+    fn test_git_adapter_reports_deleted_line() {
+        let temp_dir = setup_test_repo_with_recreated_line().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
 
-        let tree_id = index.write_tree()?;
-        let tree = repo.find_tree(tree_id)?;
-        let parent_commit = repo.find_commit(initial_commit)?;
-        let signature2 =
-            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(2000, 0))?;
+        let history = adapter
+            .get_line_history("config.txt", 3, &HistoryOptions::default())
+            .unwrap();
 
-        let second_commit = repo.commit(
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(history.entries[0].subject, "Remove flag");
+        assert_eq!(history.entries[0].change_type, ChangeType::Deleted);
+        assert_eq!(history.entries[0].content, "flag = false");
+        // Where the line was before it was deleted
+        assert_eq!(history.entries[0].line_number, 2);
+        assert_eq!(history.entries[1].subject, "Restore flag");
+        assert_eq!(history.entries[1].change_type, ChangeType::Created);
+    }
+
+    #[test]
+    fn test_git_adapter_attaches_hunks() {
+        let temp_dir = setup_test_repo_with_recreated_line().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+        let history = adapter
+            .get_line_history("config.txt", 3, &HistoryOptions::default())
+            .unwrap();
+        assert!(history.entries.iter().all(|entry| entry.hunks.is_empty()));
+
+        let adapter = GitAdapter::new(temp_dir.path())
+            .unwrap()
+            .with_patch(Some(1));
+        let history = adapter
+            .get_line_history("config.txt", 3, &HistoryOptions::default())
+            .unwrap();
+        let render = |hunk: &DiffHunk| {
+            let mut lines = vec![hunk.header()];
+            lines.extend(
+                hunk.lines
+                    .iter()
+                    .map(|line| format!("{}{}", line.kind.prefix(), line.content)),
+            );
+            lines
+        };
+
+        // The deletion, found on the side of the parent
+        assert_eq!(history.entries[0].change_type, ChangeType::Deleted);
+        assert_eq!(
+            render(&history.entries[0].hunks[0]),
+            vec!["@@ -1,3 +1,2 @@", " a", "-flag = false", " b"]
+        );
+        assert_eq!(history.entries[1].change_type, ChangeType::Created);
+        assert_eq!(
+            render(&history.entries[1].hunks[0]),
+            vec!["@@ -2,1 +2,2 @@", " b", "+flag = false"]
+        );
+    }
+
+    #[test]
+    fn test_git_adapter_follows_deleted_line() {
+        let temp_dir = setup_test_repo_with_recreated_line().unwrap();
+        let adapter = GitAdapter::new(temp_dir.path())
+            .unwrap()
+            .with_follow_deleted(true);
+
+        let history = adapter
+            .get_line_history("config.txt", 3, &HistoryOptions::default())
+            .unwrap();
+
+        let change_types: Vec<ChangeType> = history
+            .entries
+            .iter()
+            .map(|entry| entry.change_type.clone())
+            .collect();
+        assert_eq!(
+            change_types,
+            vec![
+                ChangeType::Created,
+                ChangeType::Modified,
+                ChangeType::Deleted,
+                ChangeType::Created
+            ]
+        );
+        assert_eq!(history.entries[0].content, "flag = true");
+    }
+
+    #[test]
+    fn test_git_adapter_reports_deleted_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_file(&repo, "old.txt", "setting = 1\n", "Add old file", 1000).unwrap();
+
+        let mut index = repo.index().unwrap();
+        index.remove_path(Path::new("old.txt")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature =
+            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(2000, 0))
+                .unwrap();
+        let parent = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.commit(
             Some("HEAD"),
-            &signature2,
-            &signature2,
-            "Update line 1 - first change",
+            &signature,
+            &signature,
+            "Delete old file",
             &tree,
-            &[&parent_commit],
-        )?;
+            &[&parent],
+        )
+        .unwrap();
+        fs::remove_file(temp_dir.path().join("old.txt")).unwrap();
+
+        commit_file(&repo, "new.txt", "setting = 1\n", "Add new file", 3000).unwrap();
+        commit_file(
+            &repo,
+            "old.txt",
+            "setting = 1\n",
+            "Bring back old file",
+            4000,
+        )
+        .unwrap();
 
-        // Third commit - modify line 1 again
-        fs::write(
-            &file_path,
-            "modified line 1 - second change\nline 2\nline 3\n",
-        )?;
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+        let history = adapter
+            .get_line_history("old.txt", 1, &HistoryOptions::default())
+            .unwrap();
 
-        let mut index = repo.index()?;
-        index.add_path(Path::new("test.txt"))?;
-        index.write()?;
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(history.entries[0].subject, "Delete old file");
+        assert_eq!(history.entries[0].change_type, ChangeType::Deleted);
+        assert_eq!(history.entries[1].subject, "Bring back old file");
+    }
 
-        let tree_id = index.write_tree()?;
-        let tree = repo.find_tree(tree_id)?;
-        let parent_commit = repo.find_commit(second_commit)?;
-        let signature3 =
-            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(3000, 0))?;
+    fn stage_file(repo: &Repository, file_name: &str, content: &str) {
+        fs::write(repo.workdir().unwrap().join(file_name), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(file_name)).unwrap();
+        index.write().unwrap();
+    }
 
+    fn rename_file(repo: &Repository, from: &str, to: &str, message: &str, time: i64) {
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        fs::create_dir_all(workdir.join(to).parent().unwrap()).unwrap();
+        fs::rename(workdir.join(from), workdir.join(to)).unwrap();
+
+        let mut index = repo.index().unwrap();
+        index.remove_path(Path::new(from)).unwrap();
+        index.add_path(Path::new(to)).unwrap();
+        index.write().unwrap();
+
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature =
+            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(time, 0))
+                .unwrap();
+        let parent = repo.head().unwrap().peel_to_commit().unwrap();
         repo.commit(
             Some("HEAD"),
-            &signature3,
-            &signature3,
-            "Update line 1 - second change",
+            &signature,
+            &signature,
+            message,
             &tree,
-            &[&parent_commit],
-        )?;
+            &[&parent],
+        )
+        .unwrap();
+    }
 
-        Ok(temp_dir)
+    #[test]
+    fn test_git_adapter_follows_renamed_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let content = "fn main() {\n    println!(\"hello\");\n}\n\nfn helper() {}\n";
+        commit_file(&repo, "main.rs", content, "Initial commit", 1000).unwrap();
+        rename_file(&repo, "main.rs", "src/app.rs", "Move into src", 2000);
+        commit_file(
+            &repo,
+            "src/app.rs",
+            &content.replace("hello", "hello, world"),
+            "Greet the world",
+            3000,
+        )
+        .unwrap();
+
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+        let history = adapter
+            .get_line_history("src/app.rs", 2, &HistoryOptions::default())
+            .unwrap();
+
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(history.entries[0].subject, "Initial commit");
+        assert_eq!(history.entries[0].change_type, ChangeType::Created);
+        assert_eq!(history.entries[0].file_path, "main.rs");
+        assert_eq!(history.entries[1].subject, "Greet the world");
+        assert_eq!(history.entries[1].file_path, "src/app.rs");
+        assert_eq!(history.entries[0].line_number, 2);
+        assert_eq!(history.entries[1].line_number, 2);
+    }
+
+    #[test]
+    fn test_git_adapter_finds_line_moved_from_other_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_file(
+            &repo,
+            "lib.rs",
+            "fn main() {}\nfn helper() -> u32 { 1 }\n",
+            "Initial commit",
+            1000,
+        )
+        .unwrap();
+        commit_file(
+            &repo,
+            "lib.rs",
+            "fn main() {}\nfn helper() -> u32 { 42 }\n",
+            "Change helper",
+            2000,
+        )
+        .unwrap();
+        stage_file(&repo, "helper.rs", "fn helper() -> u32 { 42 }\n");
+        commit_file(&repo, "lib.rs", "fn main() {}\n", "Split helper out", 3000).unwrap();
+
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+        let history = adapter
+            .get_line_history("helper.rs", 1, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].change_type, ChangeType::Created);
+
+        let adapter = adapter.with_find_copies(true);
+        let history = adapter
+            .get_line_history("helper.rs", 1, &HistoryOptions::default())
+            .unwrap();
+
+        assert_eq!(history.entries.len(), 3);
+        assert_eq!(history.entries[0].subject, "Initial commit");
+        assert_eq!(history.entries[0].change_type, ChangeType::Created);
+        assert_eq!(history.entries[0].file_path, "lib.rs");
+        assert_eq!(history.entries[1].subject, "Change helper");
+        assert_eq!(history.entries[1].file_path, "lib.rs");
+        assert_eq!(history.entries[2].subject, "Split helper out");
+        assert_eq!(history.entries[2].change_type, ChangeType::Moved);
+        assert_eq!(history.entries[2].file_path, "helper.rs");
+    }
+
+    #[test]
+    fn test_git_adapter_finds_line_copied_from_other_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_file(
+            &repo,
+            "a.rs",
+            "const LIMIT: usize = 10;\nfn a() {}\n",
+            "Initial commit",
+            1000,
+        )
+        .unwrap();
+        stage_file(&repo, "b.rs", "const LIMIT: usize = 10;\n");
+        commit_file(
+            &repo,
+            "a.rs",
+            "const LIMIT: usize = 10;\nfn a() { todo!() }\n",
+            "Share limit",
+            2000,
+        )
+        .unwrap();
+
+        let adapter = GitAdapter::new(temp_dir.path())
+            .unwrap()
+            .with_find_copies(true);
+        let history = adapter
+            .get_line_history("b.rs", 1, &HistoryOptions::default())
+            .unwrap();
+
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(history.entries[0].file_path, "a.rs");
+        assert_eq!(history.entries[0].change_type, ChangeType::Created);
+        assert_eq!(history.entries[1].file_path, "b.rs");
+        assert_eq!(history.entries[1].change_type, ChangeType::Copied);
+    }
+
+    #[test]
+    fn test_git_adapter_ignores_whitespace_changes() {
+        let temp_dir = setup_test_repo().unwrap();
+        let repo = Repository::open(temp_dir.path()).unwrap();
+        commit_file(&repo, "ws.rs", "x();\ny();\n", "Add calls", 1000).unwrap();
+        commit_file(&repo, "ws.rs", "    x();\n    y();\n", "Reindent", 2000).unwrap();
+        commit_file(&repo, "ws.rs", "    x(1);\n    y();\n", "Pass 1", 3000).unwrap();
+
+        let history = GitAdapter::new(temp_dir.path())
+            .unwrap()
+            .get_line_history("ws.rs", 1, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.entries.len(), 3);
+
+        let messages = |adapter: GitAdapter| -> Vec<String> {
+            adapter
+                .get_line_history("ws.rs", 1, &HistoryOptions::default())
+                .unwrap()
+                .entries
+                .into_iter()
+                .map(|entry| entry.subject)
+                .collect()
+        };
+        let adapter = GitAdapter::new(temp_dir.path())
+            .unwrap()
+            .with_ignore_whitespace(true);
+        assert_eq!(messages(adapter), vec!["Add calls", "Pass 1"]);
+
+        // Adding indentation is more than a change in its amount
+        let adapter = GitAdapter::new(temp_dir.path())
+            .unwrap()
+            .with_ignore_space_change(true);
+        assert_eq!(messages(adapter), vec!["Add calls", "Reindent", "Pass 1"]);
+    }
+
+    #[test]
+    fn test_git_adapter_ignores_space_change() {
+        let temp_dir = setup_test_repo().unwrap();
+        let repo = Repository::open(temp_dir.path()).unwrap();
+        commit_file(&repo, "ws.rs", "let a = 1;\n", "Add a", 1000).unwrap();
+        commit_file(&repo, "ws.rs", "let a  =  1;  \n", "Respace", 2000).unwrap();
+
+        let adapter = GitAdapter::new(temp_dir.path())
+            .unwrap()
+            .with_ignore_space_change(true);
+        let history = adapter
+            .get_line_history("ws.rs", 1, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].subject, "Add a");
+        assert_eq!(history.entries[0].content, "let a = 1;");
+    }
+
+    #[test]
+    fn test_git_adapter_ignores_blank_lines() {
+        let temp_dir = setup_test_repo().unwrap();
+        let repo = Repository::open(temp_dir.path()).unwrap();
+        commit_file(&repo, "blank.rs", "first();\nsecond();\n", "Add", 1000).unwrap();
+        commit_file(
+            &repo,
+            "blank.rs",
+            "\nfirst(1);\nsecond();\n",
+            "Space out",
+            2000,
+        )
+        .unwrap();
+
+        let history = GitAdapter::new(temp_dir.path())
+            .unwrap()
+            .get_line_history("blank.rs", 2, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].change_type, ChangeType::Created);
+
+        let history = GitAdapter::new(temp_dir.path())
+            .unwrap()
+            .with_ignore_blank_lines(true)
+            .get_line_history("blank.rs", 2, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(history.entries[0].subject, "Add");
+        assert_eq!(history.entries[1].change_type, ChangeType::Modified);
     }
 
     #[test]
-    fn test_git_adapter_creation() {
-        let temp_dir = setup_test_repo().unwrap();
-        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
-
-        // Test that adapter was created successfully
-        assert!(adapter.repository.path().exists());
+    fn test_collapse_whitespace() {
+        assert_eq!(collapse_whitespace("  let  a =\t1;  "), " let a = 1;");
+        assert_eq!(collapse_whitespace(""), "");
     }
 
     #[test]
-    fn test_git_adapter_get_line_history() {
+    fn test_git_adapter_line_range_history() {
         let temp_dir = setup_test_repo().unwrap();
+        let repo = Repository::open(temp_dir.path()).unwrap();
+        commit_file(
+            &repo,
+            "lib.rs",
+            "fn a() {\n    1\n}\n\nfn b() {\n    2\n}\n",
+            "Add a and b",
+            1000,
+        )
+        .unwrap();
+        commit_file(
+            &repo,
+            "lib.rs",
+            "fn a() {\n    10\n}\n\nfn b() {\n    2\n}\n",
+            "Change a",
+            2000,
+        )
+        .unwrap();
+        commit_file(
+            &repo,
+            "lib.rs",
+            "fn a() {\n    10\n}\n\nfn b() {\n    20\n}\n",
+            "Change b",
+            3000,
+        )
+        .unwrap();
         let adapter = GitAdapter::new(temp_dir.path()).unwrap();
 
+        let messages = |selector: &str| -> Vec<String> {
+            let history = adapter
+                .get_line_range_history(
+                    "lib.rs",
+                    &selector.parse().unwrap(),
+                    &HistoryOptions::default(),
+                )
+                .unwrap();
+            history
+                .entries
+                .into_iter()
+                .map(|entry| entry.subject)
+                .collect()
+        };
+
+        assert_eq!(messages(":a"), vec!["Add a and b", "Change a"]);
+        assert_eq!(messages("/fn b/,+3"), vec!["Add a and b", "Change b"]);
+        assert_eq!(messages("2,6"), vec!["Add a and b", "Change a", "Change b"]);
+
         let history = adapter
-            .get_line_history("test.txt", 1, SortOrder::Asc, &[], None, None)
+            .get_line_range_history(
+                "lib.rs",
+                &"1,3".parse().unwrap(),
+                &HistoryOptions::default(),
+            )
             .unwrap();
-
-        assert_eq!(history.file_path, "test.txt");
         assert_eq!(history.line_number, 1);
-        assert_eq!(history.entries.len(), 1);
-        assert_eq!(history.entries[0].author, "Test User");
-        assert_eq!(history.entries[0].message, "Initial commit");
+        assert_eq!(history.end_line_number, Some(3));
+        assert_eq!(history.entries[0].content, "fn a() {\n    1\n}");
+        assert_eq!(history.entries[0].change_type, ChangeType::Created);
+        assert_eq!(history.entries[1].content, "    10");
+        assert_eq!(history.entries[1].change_type, ChangeType::Modified);
+
+        let error = adapter
+            .get_line_range_history(
+                "lib.rs",
+                &"5,9".parse().unwrap(),
+                &HistoryOptions::default(),
+            )
+            .unwrap_err();
+        assert!(error.to_string().contains("Lines 5-9 are out of range"));
     }
 
     #[test]
-    fn test_git_adapter_nonexistent_file() {
+    fn test_git_adapter_file_history() {
         let temp_dir = setup_test_repo().unwrap();
+        let repo = Repository::open(temp_dir.path()).unwrap();
+        commit_file(&repo, "lib.rs", "a\nb\nc\n", "Add lines", 1000).unwrap();
+        commit_file(&repo, "lib.rs", "a\nB\nc\n", "Change b", 2000).unwrap();
+        commit_file(&repo, "lib.rs", "top\na\nB\nc\n", "Add top", 3000).unwrap();
+        commit_file(&repo, "lib.rs", "top\na\nBB\nc\n", "Change b again", 4000).unwrap();
         let adapter = GitAdapter::new(temp_dir.path()).unwrap();
 
-        let result =
-            adapter.get_line_history("nonexistent.txt", 1, SortOrder::Asc, &[], None, None);
-        assert!(result.is_err());
+        let history = adapter
+            .get_file_history("lib.rs", &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.file_path, "lib.rs");
+        assert_eq!(history.line_count(), 4);
+
+        let messages = |line: &FileLine| -> Vec<String> {
+            line.entries
+                .iter()
+                .map(|entry| entry.subject.clone())
+                .collect()
+        };
+        assert_eq!(messages(&history.lines[0]), vec!["Add top"]);
+        assert_eq!(messages(&history.lines[1]), vec!["Add lines"]);
+        assert_eq!(
+            messages(&history.lines[2]),
+            vec!["Add lines", "Change b", "Change b again"]
+        );
+        assert_eq!(history.lines[2].content, "BB");
+        assert_eq!(history.lines[3].line_number, 4);
+
+        // The limit applies to each line
+        let options = HistoryOptions {
+            limit: Some(1),
+            ..Default::default()
+        };
+        let history = adapter.get_file_history("lib.rs", &options).unwrap();
+        assert_eq!(messages(&history.lines[1]), vec!["Add lines"]);
+        assert_eq!(messages(&history.lines[2]), vec!["Change b again"]);
+
+        // Uncommitted lines are part of the file
+        fs::write(temp_dir.path().join("lib.rs"), "top\na\nBB\nc\nd\n").unwrap();
+        let history = adapter
+            .get_file_history("lib.rs", &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.line_count(), 5);
+        assert_eq!(history.lines[4].content, "d");
+        assert_eq!(history.lines[4].entries[0].author, "Not Committed Yet");
+        assert_eq!(history.lines[3].entries.len(), 1);
     }
 
     #[test]
-    fn test_git_adapter_multiple_commit_history() {
+    fn test_git_adapter_caches_diffs() {
         let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
-        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+        let options = HistoryOptions::default();
 
-        let history = adapter
-            .get_line_history("test.txt", 1, SortOrder::Asc, &[], None, None)
+        let uncached = GitAdapter::new(temp_dir.path()).unwrap().with_cache(false);
+        let expected = uncached.get_line_history("test.txt", 1, &options).unwrap();
+        assert_eq!(uncached.cache().stats().unwrap().diffs, 0);
+
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+        let history = adapter.get_line_history("test.txt", 1, &options).unwrap();
+        assert_eq!(history, expected);
+        let stats = adapter.cache().stats().unwrap();
+        assert_eq!(stats.location, temp_dir.path().join(".git").join("ombl"));
+        assert!(stats.diffs > 0);
+        assert!(stats.lines > 0);
+
+        // Later runs read the same results back
+        let history = adapter.get_line_history("test.txt", 1, &options).unwrap();
+        assert_eq!(history, expected);
+        assert_eq!(adapter.cache().stats().unwrap(), stats);
+
+        // Linked worktrees use the cache of the main repository
+        let worktrees_dir = TempDir::new().unwrap();
+        let worktree_path = worktrees_dir.path().join("linked");
+        Repository::open(temp_dir.path())
+            .unwrap()
+            .worktree("linked", &worktree_path, None)
+            .unwrap();
+        let worktree_adapter = GitAdapter::new(&worktree_path).unwrap();
+        let history = worktree_adapter
+            .get_line_history("test.txt", 1, &options)
             .unwrap();
+        assert_eq!(history.entries, expected.entries);
+        assert_eq!(worktree_adapter.cache().stats().unwrap(), stats);
 
-        assert_eq!(history.file_path, "test.txt");
-        assert_eq!(history.line_number, 1);
+        adapter.cache().clear().unwrap();
+        assert_eq!(adapter.cache().stats().unwrap().diffs, 0);
+    }
 
-        // Debug output
-        println!("Found {} entries:", history.entries.len());
-        for (i, entry) in history.entries.iter().enumerate() {
-            println!(
-                "  {}: {} - {}",
-                i,
-                entry.commit_hash[..8].to_string(),
-                entry.message
-            );
-        }
+    #[test]
+    fn test_git_adapter_reads_line_mappings_from_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let first = commit_file(&repo, "test.txt", "a\n", "Add a", 1000).unwrap();
+        let second = commit_file(&repo, "test.txt", "b\n", "Change a", 2000).unwrap();
 
-        // This should fail initially - we expect 3 commits but only get 1
-        assert_eq!(history.entries.len(), 3);
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+        let options = HistoryOptions::default();
+        let history = adapter.get_line_history("test.txt", 1, &options).unwrap();
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(adapter.cache().stats().unwrap().lines, 2);
+
+        // Lines are mapped through what the cache holds rather than diffed again
+        let mut mappings = LineMappings::new();
+        mappings.entry("test.txt".to_string()).or_default().insert(
+            1,
+            vec![ParentOrigin {
+                parent: first,
+                file_path: "test.txt".to_string(),
+                origin: LineOrigin::Unchanged(1),
+            }],
+        );
+        adapter
+            .cache()
+            .store_line_mappings(second, adapter.line_mapping(), &mappings);
+        let history = adapter.get_line_history("test.txt", 1, &options).unwrap();
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].subject, "Add a");
 
-        // Verify the entries are in chronological order (oldest first)
-        assert_eq!(history.entries[0].message, "Initial commit");
-        assert_eq!(history.entries[1].message, "Update line 1 - first change");
-        assert_eq!(history.entries[2].message, "Update line 1 - second change");
+        // Other settings map lines on their own
+        let history = adapter
+            .with_ignore_whitespace(true)
+            .get_line_history("test.txt", 1, &options)
+            .unwrap();
+        assert_eq!(history.entries.len(), 2);
     }
 
     #[test]
-    fn test_git_adapter_sort_order() {
-        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
+    fn test_git_adapter_limit_with_date_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_file(&repo, "test.txt", "a\n", "Create", 1000).unwrap();
+        commit_file(&repo, "test.txt", "b\n", "Newest by date", 3000).unwrap();
+        commit_file(&repo, "test.txt", "c\n", "Newest in ancestry", 2000).unwrap();
+
         let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+        let newest = |order| {
+            let options = HistoryOptions {
+                order,
+                limit: Some(1),
+                ..Default::default()
+            };
+            let history = adapter.get_line_history("test.txt", 1, &options).unwrap();
+            assert_eq!(history.entries.len(), 1);
+            history.entries[0].subject.clone()
+        };
+
+        assert_eq!(newest(CommitOrder::Topo), "Newest in ancestry");
+        // The limit keeps the newest changes by date, wherever they are
+        assert_eq!(newest(CommitOrder::AuthorDate), "Newest by date");
+        assert_eq!(newest(CommitOrder::CommitterDate), "Newest by date");
+    }
 
-        // Test ascending order (oldest first)
-        let history_asc = adapter
-            .get_line_history("test.txt", 1, SortOrder::Asc, &[], None, None)
-            .unwrap();
+    /// Commits a single file on top of the given parents without touching the
+    /// working tree, updating `update_ref` if given.
+    fn commit_on(
+        repo: &Repository,
+        update_ref: Option<&str>,
+        parents: &[git2::Oid],
+        content: &str,
+        message: &str,
+        time: i64,
+    ) -> git2::Oid {
+        let blob = repo.blob(content.as_bytes()).unwrap();
+        let mut builder = repo.treebuilder(None).unwrap();
+        builder.insert("test.txt", blob, 0o100644).unwrap();
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let signature =
+            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(time, 0))
+                .unwrap();
+        let parents: Vec<git2::Commit> = parents
+            .iter()
+            .map(|&parent| repo.find_commit(parent).unwrap())
+            .collect();
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
 
-        // Test descending order (newest first)
-        let history_desc = adapter
-            .get_line_history("test.txt", 1, SortOrder::Desc, &[], None, None)
-            .unwrap();
+        repo.commit(
+            update_ref,
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parent_refs,
+        )
+        .unwrap()
+    }
 
-        // Both should have the same number of entries
-        assert_eq!(history_asc.entries.len(), history_desc.entries.len());
-        assert_eq!(history_asc.entries.len(), 3);
+    fn setup_test_repo_with_merge() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+
+        let base = commit_on(&repo, None, &[], "a\nb\nc\n", "Base", 1000);
+        let side = commit_on(&repo, None, &[base], "a\nB\nc\n", "Change b on side", 2000);
+        let main = commit_on(&repo, None, &[base], "a\nb\nC\n", "Change c", 3000);
+        // The merge also rewrites line 1 itself
+        let merge = commit_on(&repo, None, &[main, side], "A\nB\nC\n", "Merge side", 4000);
+        repo.reference("refs/heads/main", merge, true, "").unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .unwrap();
 
-        // Ascending order: oldest first
-        assert_eq!(history_asc.entries[0].message, "Initial commit");
-        assert_eq!(
-            history_asc.entries[1].message,
-            "Update line 1 - first change"
-        );
-        assert_eq!(
-            history_asc.entries[2].message,
-            "Update line 1 - second change"
-        );
+        temp_dir
+    }
 
-        // Descending order: newest first
+    #[test]
+    fn test_git_adapter_merges() {
+        let temp_dir = setup_test_repo_with_merge();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+        let messages = |adapter: &GitAdapter, line: u32, options: &HistoryOptions| -> Vec<String> {
+            adapter
+                .get_line_history("test.txt", line, options)
+                .unwrap()
+                .entries
+                .into_iter()
+                .map(|entry| entry.subject)
+                .collect()
+        };
+        let options = HistoryOptions::default();
+
+        // Changes merged in are attributed to the side they were made on
         assert_eq!(
-            history_desc.entries[0].message,
-            "Update line 1 - second change"
+            messages(&adapter, 2, &options),
+            vec!["Base", "Change b on side"]
         );
+        assert_eq!(messages(&adapter, 3, &options), vec!["Base", "Change c"]);
+        // A merge that changes the line itself is reported
+        assert_eq!(messages(&adapter, 1, &options), vec!["Base", "Merge side"]);
+
+        let no_merges = HistoryOptions {
+            no_merges: true,
+            ..Default::default()
+        };
+        assert_eq!(messages(&adapter, 1, &no_merges), vec!["Base"]);
         assert_eq!(
-            history_desc.entries[1].message,
-            "Update line 1 - first change"
+            messages(&adapter, 2, &no_merges),
+            vec!["Base", "Change b on side"]
         );
-        assert_eq!(history_desc.entries[2].message, "Initial commit");
 
-        // Verify descending order has later timestamps first
-        assert!(history_desc.entries[0].timestamp >= history_desc.entries[1].timestamp);
-        assert!(history_desc.entries[1].timestamp >= history_desc.entries[2].timestamp);
+        // Along the first parent, the merge brought the change in
+        let adapter = GitAdapter::new(temp_dir.path())
+            .unwrap()
+            .with_first_parent(true);
+        assert_eq!(messages(&adapter, 2, &options), vec!["Base", "Merge side"]);
+        assert_eq!(messages(&adapter, 3, &options), vec!["Base", "Change c"]);
     }
 
     #[test]
-    fn test_git_adapter_ignore_single_revision() {
-        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
-        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
-
-        // First get all commits to find one to ignore
-        let history_all = adapter
-            .get_line_history("test.txt", 1, SortOrder::Asc, &[], None, None)
-            .unwrap();
-
-        assert_eq!(history_all.entries.len(), 3);
-
-        // Get the hash of the second commit to ignore
-        let ignore_hash = &history_all.entries[1].commit_hash[..8]; // Use abbreviated hash
-        let ignore_revs = vec![ignore_hash.to_string()];
-
-        // Test with ignored revision
-        let history_filtered = adapter
-            .get_line_history("test.txt", 1, SortOrder::Asc, &ignore_revs, None, None)
-            .unwrap();
+    fn test_git_adapter_commit_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_file(&repo, "test.txt", "first\nline 2\nline 3\n", "Create", 2000).unwrap();
 
-        // Should have one less commit
-        assert_eq!(history_filtered.entries.len(), 2);
+        // Rebased on top, but authored long before
+        fs::write(
+            temp_dir.path().join("test.txt"),
+            "rebased\nline 2\nline 3\n",
+        )
+        .unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("test.txt")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let author =
+            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(500, 0))
+                .unwrap();
+        let committer =
+            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(3000, 0))
+                .unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &author,
+            &committer,
+            "Rebased",
+            &tree,
+            &[&head],
+        )
+        .unwrap();
 
-        // Verify the ignored commit is not present
-        for entry in &history_filtered.entries {
-            assert!(!entry.commit_hash.starts_with(ignore_hash));
-        }
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+        let history = |order| {
+            let options = HistoryOptions {
+                order,
+                ..Default::default()
+            };
+            adapter.get_line_history("test.txt", 1, &options).unwrap()
+        };
+
+        let topo = history(CommitOrder::Topo);
+        let messages: Vec<&str> = topo.entries.iter().map(|e| e.subject.as_str()).collect();
+        assert_eq!(messages, vec!["Create", "Rebased"]);
+        assert_eq!(topo.entries[0].change_type, ChangeType::Created);
+
+        let by_author = history(CommitOrder::AuthorDate);
+        let messages: Vec<&str> = by_author
+            .entries
+            .iter()
+            .map(|e| e.subject.as_str())
+            .collect();
+        assert_eq!(messages, vec!["Rebased", "Create"]);
+        // Dates do not change which commit created the line
+        assert_eq!(by_author.entries[1].change_type, ChangeType::Created);
+
+        let by_committer = history(CommitOrder::CommitterDate);
+        assert_eq!(by_committer.entries, topo.entries);
+
+        // The latest change to a line of the file follows the order too
+        let latest = |sort_order| {
+            let options = HistoryOptions {
+                sort_order,
+                ..Default::default()
+            };
+            let file = adapter.get_file_history("test.txt", &options).unwrap();
+            file.lines[0].latest_entry().unwrap().subject.clone()
+        };
+        assert_eq!(latest(SortOrder::Asc), "Rebased");
+        assert_eq!(latest(SortOrder::Desc), "Rebased");
     }
 
     #[test]
-    fn test_git_adapter_ignore_multiple_revisions() {
-        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
+    fn test_git_adapter_reports_identities() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_file(
+            &repo,
+            "test.txt",
+            "line 1\n",
+            "Pair on line 1\n\nCo-authored-by: Jane Smith <jane@example.com>\n",
+            1000,
+        )
+        .unwrap();
         let adapter = GitAdapter::new(temp_dir.path()).unwrap();
 
-        // First get all commits
-        let history_all = adapter
-            .get_line_history("test.txt", 1, SortOrder::Asc, &[], None, None)
-            .unwrap();
-
-        assert_eq!(history_all.entries.len(), 3);
-
-        // Ignore first and third commits
-        let ignore_revs = vec![
-            history_all.entries[0].commit_hash[..8].to_string(),
-            history_all.entries[2].commit_hash[..8].to_string(),
-        ];
-
-        let history_filtered = adapter
-            .get_line_history("test.txt", 1, SortOrder::Asc, &ignore_revs, None, None)
+        let history = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
             .unwrap();
-
-        // Should have only one commit remaining
-        assert_eq!(history_filtered.entries.len(), 1);
+        let entry = &history.entries[0];
+        assert_eq!(entry.author, "Test User");
+        assert_eq!(entry.author_email, "test@example.com");
+        assert_eq!(entry.committer, "Test User");
+        assert_eq!(entry.committer_email, "test@example.com");
+        assert_eq!(entry.committer_timestamp.timestamp(), 1000);
         assert_eq!(
-            history_filtered.entries[0].message,
-            "Update line 1 - first change"
+            entry.co_authors,
+            vec![Person {
+                name: "Jane Smith".to_string(),
+                email: "jane@example.com".to_string(),
+            }]
         );
     }
 
     #[test]
-    fn test_git_adapter_ignore_nonexistent_revision() {
-        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
-        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
-
-        // Use a fake hash that doesn't exist
-        let ignore_revs = vec!["fakehash123".to_string()];
-
-        let history_normal = adapter
-            .get_line_history("test.txt", 1, SortOrder::Asc, &[], None, None)
+    fn test_git_adapter_keeps_timezones() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        fs::write(temp_dir.path().join("test.txt"), "line 1\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("test.txt")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let author =
+            git2::Signature::new("Author", "a@example.com", &git2::Time::new(1000, 120)).unwrap();
+        let committer =
+            git2::Signature::new("Committer", "c@example.com", &git2::Time::new(2000, -300))
+                .unwrap();
+        repo.commit(Some("HEAD"), &author, &committer, "Add", &tree, &[])
             .unwrap();
 
-        let history_with_fake_ignore = adapter
-            .get_line_history("test.txt", 1, SortOrder::Asc, &ignore_revs, None, None)
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+        let history = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
             .unwrap();
-
-        // Should have the same number of commits since fake hash doesn't match anything
+        let entry = &history.entries[0];
+        assert_eq!(entry.timestamp.to_rfc3339(), "1970-01-01T02:16:40+02:00");
         assert_eq!(
-            history_normal.entries.len(),
-            history_with_fake_ignore.entries.len()
+            entry.committer_timestamp.to_rfc3339(),
+            "1969-12-31T19:33:20-05:00"
         );
     }
 
     #[test]
-    fn test_git_adapter_parse_date_iso8601() {
-        let temp_dir = setup_test_repo().unwrap();
+    fn test_git_adapter_applies_mailmap() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_file(
+            &repo,
+            "test.txt",
+            "line 1\n",
+            "Pair on line 1\n\nCo-authored-by: jane <jane@old.example.com>\n",
+            1000,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join(".mailmap"),
+            "Canonical User <user@example.com> <test@example.com>\n\
+             Jane Smith <jane@example.com> <jane@old.example.com>\n",
+        )
+        .unwrap();
+
         let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+        let history = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+        let entry = &history.entries[0];
+        assert_eq!(entry.author, "Canonical User");
+        assert_eq!(entry.author_email, "user@example.com");
+        assert_eq!(entry.committer, "Canonical User");
+        assert_eq!(
+            entry.co_authors[0].to_string(),
+            "Jane Smith <jane@example.com>"
+        );
 
-        // Test parsing various ISO 8601 formats
-        let iso_date = "2023-01-01T00:00:00Z";
-        let parsed = adapter.parse_git_date(iso_date).unwrap();
-        assert_eq!(parsed.timestamp(), 1672531200); // 2023-01-01 UTC
+        // Names are shown as recorded without the mailmap
+        let adapter = GitAdapter::new(temp_dir.path())
+            .unwrap()
+            .with_mailmap(false);
+        let history = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+        let entry = &history.entries[0];
+        assert_eq!(entry.author, "Test User");
+        assert_eq!(entry.author_email, "test@example.com");
+        assert_eq!(
+            entry.co_authors[0].to_string(),
+            "jane <jane@old.example.com>"
+        );
 
-        let iso_with_tz = "2023-01-01T09:00:00+09:00";
-        let parsed_tz = adapter.parse_git_date(iso_with_tz).unwrap();
-        assert_eq!(parsed_tz.timestamp(), 1672531200); // Same UTC time
+        // A mailmap configured with mailmap.file is used as well
+        fs::remove_file(temp_dir.path().join(".mailmap")).unwrap();
+        let mailmap_path = temp_dir.path().join("people.mailmap");
+        fs::write(&mailmap_path, "Configured User <test@example.com>\n").unwrap();
+        repo.config()
+            .unwrap()
+            .set_str("mailmap.file", mailmap_path.to_str().unwrap())
+            .unwrap();
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+        let history = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(history.entries[0].author, "Configured User");
+        assert_eq!(history.entries[0].author_email, "test@example.com");
     }
 
     #[test]
-    fn test_git_adapter_parse_date_simple_formats() {
-        let temp_dir = setup_test_repo().unwrap();
-        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+    fn test_git_adapter_keeps_co_authors_without_name() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_file(
+            &repo,
+            "test.txt",
+            "line 1\n",
+            "Pair on line 1\n\nCo-authored-by: <bob@example.com>\n",
+            1000,
+        )
+        .unwrap();
 
-        // Test simple date format
-        let simple_date = "2023-01-01";
-        let parsed = adapter.parse_git_date(simple_date).unwrap();
-        assert_eq!(parsed.format("%Y-%m-%d").to_string(), "2023-01-01");
-
-        // Test datetime format
-        let datetime = "2023-01-01 12:00:00";
-        let parsed_dt = adapter.parse_git_date(datetime).unwrap();
-        assert_eq!(parsed_dt.format("%H").to_string(), "12");
+        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
+        let history = adapter
+            .get_line_history("test.txt", 1, &HistoryOptions::default())
+            .unwrap();
+        let co_authors = &history.entries[0].co_authors;
+        assert_eq!(co_authors.len(), 1);
+        assert_eq!(co_authors[0].name, "");
+        assert_eq!(co_authors[0].email, "bob@example.com");
     }
 
     #[test]
-    fn test_git_adapter_parse_date_formats() {
-        let temp_dir = setup_test_repo().unwrap();
-        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
-
-        // Test additional formats
-        let iso_local = "2023-01-01T00:00:00";
-        let parsed = adapter.parse_git_date(iso_local);
-        assert!(parsed.is_err()); // Should fail without timezone
-
-        // Test error case
-        let invalid_date = "not-a-date";
-        let result = adapter.parse_git_date(invalid_date);
-        assert!(result.is_err());
+    fn test_co_authors_without_email() {
+        let trailer = |value: &str| Trailer {
+            key: "Co-authored-by".to_string(),
+            value: value.to_string(),
+        };
+        let trailers = vec![
+            trailer("  Jane Doe "),
+            trailer("Bob <bob@example.com>"),
+            trailer(""),
+        ];
+        assert_eq!(
+            co_authors(&trailers),
+            vec![
+                Person {
+                    name: "Jane Doe".to_string(),
+                    email: String::new(),
+                },
+                Person {
+                    name: "Bob".to_string(),
+                    email: "bob@example.com".to_string(),
+                },
+            ]
+        );
     }
 
     #[test]
-    fn test_git_adapter_filter_by_since_date() {
-        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
-        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
-
-        // Get all commits first
-        let history_all = adapter
-            .get_line_history("test.txt", 1, SortOrder::Asc, &[], None, None)
-            .unwrap();
-
-        assert_eq!(history_all.entries.len(), 3);
-
-        // Filter to only show commits from a specific date onwards
-        // Use a timestamp between the first and second commit
-        let since_date = "1970-01-01T00:25:00Z"; // 1500 seconds epoch
-        let history_filtered = adapter
-            .get_line_history("test.txt", 1, SortOrder::Asc, &[], Some(since_date), None)
-            .unwrap();
+    fn test_parse_message() {
+        let message = "Fix parser\n\nCo-authored-by: Jane <jane@example.com>\n\nco-authored-by: Bob Smith <bob@example.com>\nSigned-off-by: Alice <alice@example.com>\nCo-Authored-By: broken\nFixes: 1234abcd (\"Add parser\n  for lines\")\n";
+        let (subject, body, trailers) = parse_message(message);
+        assert_eq!(subject, "Fix parser");
+        assert_eq!(body, "Co-authored-by: Jane <jane@example.com>");
+        assert_eq!(
+            trailers.iter().map(|t| t.to_string()).collect::<Vec<_>>(),
+            vec![
+                "co-authored-by: Bob Smith <bob@example.com>",
+                "Signed-off-by: Alice <alice@example.com>",
+                "Co-Authored-By: broken",
+                "Fixes: 1234abcd (\"Add parser for lines\")",
+            ]
+        );
+        assert_eq!(
+            co_authors(&trailers),
+            vec![
+                Person {
+                    name: "Bob Smith".to_string(),
+                    email: "bob@example.com".to_string(),
+                },
+                Person {
+                    name: "broken".to_string(),
+                    email: String::new(),
+                },
+            ]
+        );
 
-        // Should have fewer commits (only those after the since date)
-        assert!(history_filtered.entries.len() <= history_all.entries.len());
-        assert!(history_filtered.entries.len() >= 2); // Should have at least 2 commits
+        // A subject wrapped over several lines is joined
+        let (subject, body, trailers) = parse_message(
+            "Fix the parser\nfor long lines\n\nIt broke.\n\nSee the docs: section 2\n",
+        );
+        assert_eq!(subject, "Fix the parser for long lines");
+        assert_eq!(body, "It broke.\n\nSee the docs: section 2");
+        assert!(trailers.is_empty());
+
+        // A message of one paragraph has no trailers
+        let (subject, body, trailers) = parse_message("Signed-off-by: Alice <alice@example.com>");
+        assert_eq!(subject, "Signed-off-by: Alice <alice@example.com>");
+        assert!(body.is_empty());
+        assert!(trailers.is_empty());
     }
 
     #[test]
-    fn test_git_adapter_filter_by_until_date() {
-        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
-        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
-
-        // Get all commits first
-        let history_all = adapter
-            .get_line_history("test.txt", 1, SortOrder::Asc, &[], None, None)
-            .unwrap();
-
-        assert_eq!(history_all.entries.len(), 3);
-
-        // Filter to only show commits up to a specific date
-        // Use a timestamp between the second and third commit
-        let until_date = "1970-01-01T00:35:00Z"; // 2100 seconds epoch
-        let history_filtered = adapter
-            .get_line_history("test.txt", 1, SortOrder::Asc, &[], None, Some(until_date))
-            .unwrap();
-
-        // Should have fewer commits (only those before the until date)
-        assert!(history_filtered.entries.len() <= history_all.entries.len());
-        assert!(history_filtered.entries.len() >= 1); // Should have at least 1 commit
+    fn test_all_lines_and_distinctive_content() {
+        let lines = all_lines(b"a\r\nb\nc\nb\n");
+        assert_eq!(lines.get(b"b".as_slice()), Some(&2));
+        assert_eq!(lines.get(b"c".as_slice()), Some(&3));
+        assert_eq!(lines.get(b"d".as_slice()), None);
+
+        assert!(is_distinctive("flag = true"));
+        assert!(!is_distinctive("    }"));
+        assert!(!is_distinctive(""));
     }
 
     #[test]
-    fn test_git_adapter_filter_by_date_range() {
-        let temp_dir = setup_test_repo_with_multiple_commits().unwrap();
-        let adapter = GitAdapter::new(temp_dir.path()).unwrap();
-
-        // Get all commits first
-        let history_all = adapter
-            .get_line_history("test.txt", 1, SortOrder::Asc, &[], None, None)
-            .unwrap();
-
-        assert_eq!(history_all.entries.len(), 3);
+    fn test_line_index() {
+        let text =
+            |content: &[u8], line_number| LineIndex::new(content).lines(content).text(line_number);
+        let content = b"first\r\nsecond\nthird";
+
+        assert_eq!(text(content, 1), Some("first".to_string()));
+        assert_eq!(text(content, 2), Some("second".to_string()));
+        assert_eq!(text(content, 3), Some("third".to_string()));
+        assert_eq!(text(content, 4), None);
+        assert_eq!(text(content, 0), None);
+        assert_eq!(text(b"only\n", 2), None);
+        assert_eq!(text(b"", 1), None);
+
+        for content in [&b""[..], b"\n", b"a", b"a\n", b"a\nb", b"a\n\nb\n"] {
+            assert_eq!(
+                LineIndex::new(content).lines(content).count(),
+                count_lines(content)
+            );
+        }
+    }
 
-        // Filter to a specific date range that should include only the middle commit
-        let since_date = "1970-01-01T00:25:00Z"; // 1500 seconds
-        let until_date = "1970-01-01T00:35:00Z"; // 2100 seconds
-        let history_filtered = adapter
-            .get_line_history(
-                "test.txt",
-                1,
-                SortOrder::Asc,
-                &[],
-                Some(since_date),
-                Some(until_date),
-            )
-            .unwrap();
+    #[test]
+    fn test_map_line_to_parent() {
+        let hunks = [
+            // Line 2 replaced by two lines
+            HunkRange {
+                old_start: 2,
+                old_lines: 1,
+                new_start: 2,
+                new_lines: 2,
+            },
+            // Old lines 5-6 removed after new line 5
+            HunkRange {
+                old_start: 5,
+                old_lines: 2,
+                new_start: 5,
+                new_lines: 0,
+            },
+        ];
 
-        // Should have exactly 1 commit (the middle one)
-        assert_eq!(history_filtered.entries.len(), 1);
-        assert_eq!(
-            history_filtered.entries[0].message,
-            "Update line 1 - first change"
-        );
+        assert_eq!(map_line_to_parent(&hunks, 1), LineOrigin::Unchanged(1));
+        assert_eq!(map_line_to_parent(&hunks, 2), LineOrigin::Changed(2));
+        assert_eq!(map_line_to_parent(&hunks, 3), LineOrigin::Added(Some(2)));
+        assert_eq!(map_line_to_parent(&hunks, 4), LineOrigin::Unchanged(3));
+        assert_eq!(map_line_to_parent(&hunks, 5), LineOrigin::Unchanged(4));
+        assert_eq!(map_line_to_parent(&hunks, 6), LineOrigin::Unchanged(7));
     }
 }
@@ -0,0 +1,299 @@
+use super::{HunkRange, ParentOrigin};
+use anyhow::Result;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DIFFS_DIR: &str = "diffs";
+const RENAMES_DIR: &str = "renames";
+const LINES_DIR: &str = "lines";
+
+/// Results of comparing git objects, kept under `.git/ombl/` between runs.
+///
+/// Entries are keyed by object ids only. Objects never change, so entries never
+/// go stale and nothing has to be invalidated. Failing to read or write the
+/// cache is never an error: the results are computed again instead.
+pub struct Cache {
+    dir: PathBuf,
+}
+
+/// How lines were compared when diffing two blobs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Comparison {
+    Exact,
+    IgnoreWhitespace,
+    IgnoreSpaceChange,
+}
+
+impl Comparison {
+    fn suffix(self) -> &'static str {
+        match self {
+            Comparison::Exact => "",
+            Comparison::IgnoreWhitespace => "-w",
+            Comparison::IgnoreSpaceChange => "-b",
+        }
+    }
+}
+
+/// The settings lines of a commit are mapped into its parents with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct LineMapping {
+    pub(super) comparison: Comparison,
+    pub(super) ignore_blank_lines: bool,
+    pub(super) first_parent: bool,
+}
+
+/// Where the lines of the files of a commit are in each of its parents, by
+/// path and line number.
+pub(super) type LineMappings = BTreeMap<String, BTreeMap<u32, Vec<ParentOrigin>>>;
+
+/// What the cache holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheStats {
+    pub location: PathBuf,
+    /// Diffs between two blobs
+    pub diffs: usize,
+    /// Renames between a commit and one of its parents
+    pub renames: usize,
+    /// Commits whose lines were mapped into their parents
+    pub lines: usize,
+    /// Size of all entries in bytes
+    pub size: u64,
+}
+
+impl Cache {
+    /// Uses the `ombl` directory of a git directory.
+    pub(super) fn new(git_dir: &Path) -> Self {
+        Self {
+            dir: git_dir.join("ombl"),
+        }
+    }
+
+    /// Counts the entries of the cache and their size.
+    pub fn stats(&self) -> Result<CacheStats> {
+        let (diffs, diffs_size) = count_entries(&self.dir.join(DIFFS_DIR))?;
+        let (renames, renames_size) = count_entries(&self.dir.join(RENAMES_DIR))?;
+        let (lines, lines_size) = count_entries(&self.dir.join(LINES_DIR))?;
+
+        Ok(CacheStats {
+            location: self.dir.clone(),
+            diffs,
+            renames,
+            lines,
+            size: diffs_size + renames_size + lines_size,
+        })
+    }
+
+    /// Removes every entry.
+    pub fn clear(&self) -> Result<()> {
+        match fs::remove_dir_all(&self.dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub(super) fn hunks(
+        &self,
+        old: git2::Oid,
+        new: git2::Oid,
+        comparison: Comparison,
+    ) -> Option<Vec<HunkRange>> {
+        self.read(&self.diff_path(old, new, comparison))
+    }
+
+    pub(super) fn store_hunks(
+        &self,
+        old: git2::Oid,
+        new: git2::Oid,
+        comparison: Comparison,
+        hunks: &[HunkRange],
+    ) {
+        self.write(&self.diff_path(old, new, comparison), &hunks);
+    }
+
+    /// The files `commit` renamed since `parent`, as pairs of old and new path.
+    pub(super) fn renames(
+        &self,
+        parent: git2::Oid,
+        commit: git2::Oid,
+    ) -> Option<Vec<(String, String)>> {
+        self.read(&self.renames_path(parent, commit))
+    }
+
+    pub(super) fn store_renames(
+        &self,
+        parent: git2::Oid,
+        commit: git2::Oid,
+        renames: &[(String, String)],
+    ) {
+        self.write(&self.renames_path(parent, commit), &renames);
+    }
+
+    /// The lines of `commit` mapped into its parents so far.
+    pub(super) fn line_mappings(
+        &self,
+        commit: git2::Oid,
+        mapping: LineMapping,
+    ) -> Option<LineMappings> {
+        self.read(&self.lines_path(commit, mapping))
+    }
+
+    pub(super) fn store_line_mappings(
+        &self,
+        commit: git2::Oid,
+        mapping: LineMapping,
+        mappings: &LineMappings,
+    ) {
+        self.write(&self.lines_path(commit, mapping), mappings);
+    }
+
+    fn diff_path(&self, old: git2::Oid, new: git2::Oid, comparison: Comparison) -> PathBuf {
+        entry_path(
+            &self.dir.join(DIFFS_DIR),
+            old,
+            &format!("{}{}", new, comparison.suffix()),
+        )
+    }
+
+    fn lines_path(&self, commit: git2::Oid, mapping: LineMapping) -> PathBuf {
+        let mut name = format!("lines{}", mapping.comparison.suffix());
+        if mapping.ignore_blank_lines {
+            name.push_str("-blank");
+        }
+        if mapping.first_parent {
+            name.push_str("-first-parent");
+        }
+        entry_path(&self.dir.join(LINES_DIR), commit, &name)
+    }
+
+    fn renames_path(&self, parent: git2::Oid, commit: git2::Oid) -> PathBuf {
+        entry_path(&self.dir.join(RENAMES_DIR), commit, &parent.to_string())
+    }
+
+    fn read<T: DeserializeOwned>(&self, path: &Path) -> Option<T> {
+        let content = fs::read(path).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    fn write<T: Serialize>(&self, path: &Path, value: &T) {
+        let Ok(content) = serde_json::to_vec(value) else {
+            return;
+        };
+        let Some(dir) = path.parent() else {
+            return;
+        };
+        if fs::create_dir_all(dir).is_err() {
+            return;
+        }
+
+        // Another process may be reading the same entry
+        let temp_path = path.with_extension(format!("tmp{}", std::process::id()));
+        if fs::write(&temp_path, content).is_err() || fs::rename(&temp_path, path).is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+    }
+}
+
+/// Spreads entries over directories named after the first byte of an object
+/// id, like git does for loose objects.
+fn entry_path(dir: &Path, oid: git2::Oid, name: &str) -> PathBuf {
+    let oid = oid.to_string();
+    let (fan_out, rest) = oid.split_at(2);
+    dir.join(fan_out).join(format!("{}-{}", rest, name))
+}
+
+/// Counts the files under a directory and adds up their size.
+fn count_entries(dir: &Path) -> Result<(usize, u64)> {
+    let read_dir = match fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((0, 0)),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut count = 0;
+    let mut size = 0;
+    for entry in read_dir {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            let (dir_count, dir_size) = count_entries(&entry.path())?;
+            count += dir_count;
+            size += dir_size;
+        } else {
+            count += 1;
+            size += metadata.len();
+        }
+    }
+
+    Ok((count, size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::LineOrigin;
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_cache_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = Cache::new(temp_dir.path());
+        let old = git2::Oid::hash_object(git2::ObjectType::Blob, b"a\n").unwrap();
+        let new = git2::Oid::hash_object(git2::ObjectType::Blob, b"b\n").unwrap();
+        let hunks = vec![HunkRange {
+            old_start: 1,
+            old_lines: 1,
+            new_start: 1,
+            new_lines: 1,
+        }];
+
+        assert_eq!(cache.hunks(old, new, Comparison::Exact), None);
+        cache.store_hunks(old, new, Comparison::Exact, &hunks);
+        assert_eq!(cache.hunks(old, new, Comparison::Exact), Some(hunks));
+        // Diffs ignoring whitespace are kept apart
+        assert_eq!(cache.hunks(old, new, Comparison::IgnoreWhitespace), None);
+
+        let renames = vec![("old.rs".to_string(), "new.rs".to_string())];
+        cache.store_renames(old, new, &renames);
+        assert_eq!(cache.renames(old, new), Some(renames));
+        assert_eq!(cache.renames(new, old), None);
+
+        let mapping = LineMapping {
+            comparison: Comparison::Exact,
+            ignore_blank_lines: false,
+            first_parent: false,
+        };
+        let mut mappings = LineMappings::new();
+        mappings.entry("new.rs".to_string()).or_default().insert(
+            3,
+            vec![ParentOrigin {
+                parent: old,
+                file_path: "old.rs".to_string(),
+                origin: LineOrigin::Unchanged(2),
+            }],
+        );
+        cache.store_line_mappings(new, mapping, &mappings);
+        assert_eq!(cache.line_mappings(new, mapping), Some(mappings));
+        // Lines mapped with other settings are kept apart
+        let first_parent = LineMapping {
+            first_parent: true,
+            ..mapping
+        };
+        assert_eq!(cache.line_mappings(new, first_parent), None);
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.location, temp_dir.path().join("ombl"));
+        assert_eq!(stats.diffs, 1);
+        assert_eq!(stats.renames, 1);
+        assert_eq!(stats.lines, 1);
+        assert!(stats.size > 0);
+
+        cache.clear().unwrap();
+        assert_eq!(cache.hunks(old, new, Comparison::Exact), None);
+        assert_eq!(cache.stats().unwrap().diffs, 0);
+        // Clearing an empty cache is fine
+        cache.clear().unwrap();
+    }
+}
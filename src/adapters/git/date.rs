@@ -0,0 +1,270 @@
+use anyhow::Result;
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Months, NaiveDate, NaiveTime, TimeZone, Utc, Weekday,
+};
+use regex::Regex;
+
+/// Parses a date the way `git log --since` and `--until` take it.
+///
+/// Besides exact RFC 3339 and RFC 2822 dates, this accepts bare dates and
+/// times such as `2023-06`, `2023-06-15` or `2023-06-15 12:30`, read in the
+/// timezone of `now` unless they carry an offset, and dates relative to `now`
+/// such as `2 weeks ago`, `3.months`, `yesterday` or `last monday`. Bare
+/// dates take the offset that applied on that date in the timezone of `now`,
+/// so local time gets daylight saving time right.
+pub(super) fn parse_date<Tz: TimeZone>(input: &str, now: DateTime<Tz>) -> Result<DateTime<Utc>> {
+    let input = input.trim();
+
+    if let Ok(date) = DateTime::parse_from_rfc3339(input) {
+        return Ok(date.to_utc());
+    }
+    if let Ok(date) = DateTime::parse_from_rfc2822(input) {
+        return Ok(date.to_utc());
+    }
+    // An RFC-like format git sometimes uses
+    if let Ok(date) = DateTime::parse_from_str(input, "%a, %d %b %Y %H:%M:%S %Z") {
+        return Ok(date.to_utc());
+    }
+
+    if let Some(date) = parse_bare_date(input, &now.timezone())? {
+        return Ok(date);
+    }
+    parse_relative_date(input, now.fixed_offset()).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Unable to parse date '{}'. Use an ISO 8601 or RFC 2822 date, a bare date like 2023-06-15 or 2023-06, or a relative date like \"2 weeks ago\", \"yesterday\" or \"last monday\"",
+            input
+        )
+    })
+}
+
+/// Parses a timezone given as an offset such as `+0200` or `-05:00`, or as
+/// `UTC`.
+pub(super) fn parse_timezone(input: &str) -> Result<FixedOffset> {
+    let invalid = || {
+        anyhow::anyhow!(
+            "Invalid timezone '{}'; use an offset such as +0200 or UTC",
+            input
+        )
+    };
+
+    if ["utc", "gmt", "z"].contains(&input.to_lowercase().as_str()) {
+        return Ok(FixedOffset::east_opt(0).unwrap());
+    }
+    let regex = Regex::new(r"^([+-])(\d{2}):?(\d{2})$").unwrap();
+    let captures = regex.captures(input).ok_or_else(invalid)?;
+    let hours: i32 = captures[2].parse()?;
+    let minutes: i32 = captures[3].parse()?;
+    let seconds = (hours * 60 + minutes) * 60;
+    let seconds = if &captures[1] == "-" {
+        -seconds
+    } else {
+        seconds
+    };
+    FixedOffset::east_opt(seconds).ok_or_else(invalid)
+}
+
+/// Parses a year, month, day and time, the parts left out being the start of
+/// the period given; `None` when the input does not look like one.
+fn parse_bare_date<Tz: TimeZone>(input: &str, timezone: &Tz) -> Result<Option<DateTime<Utc>>> {
+    let regex = Regex::new(
+        r"(?i)^(\d{4})(?:-(\d{1,2})(?:-(\d{1,2})(?:[ T](\d{1,2}):(\d{2})(?::(\d{2}))?)?)?)?(?:\s*(Z|UTC|GMT|[+-]\d{2}:?\d{2}))?$",
+    )
+    .unwrap();
+    let Some(captures) = regex.captures(input) else {
+        return Ok(None);
+    };
+    let part = |index: usize| -> u32 {
+        captures
+            .get(index)
+            .map_or(0, |part| part.as_str().parse().unwrap_or(u32::MAX))
+    };
+    let offset = captures
+        .get(7)
+        .map(|offset| parse_timezone(offset.as_str()))
+        .transpose()?;
+
+    NaiveDate::from_ymd_opt(part(1) as i32, part(2).max(1), part(3).max(1))
+        .zip(NaiveTime::from_hms_opt(part(4), part(5), part(6)))
+        .and_then(|(date, time)| {
+            let date = date.and_time(time);
+            match offset {
+                Some(offset) => offset
+                    .from_local_datetime(&date)
+                    .single()
+                    .map(|d| d.to_utc()),
+                // The earlier of the two times repeated when clocks go back
+                None => timezone
+                    .from_local_datetime(&date)
+                    .earliest()
+                    .map(|d| d.to_utc()),
+            }
+        })
+        .map(Some)
+        .ok_or_else(|| anyhow::anyhow!("Invalid date '{}'", input))
+}
+
+/// Parses words like `2 weeks ago`, `3.months`, `yesterday` or
+/// `last monday`, going back from `now`.
+fn parse_relative_date(input: &str, now: DateTime<FixedOffset>) -> Option<DateTime<Utc>> {
+    let input = input.to_lowercase();
+    let words: Vec<&str> = input
+        .split(|c: char| c.is_whitespace() || c == '.' || c == ',')
+        .filter(|word| !word.is_empty())
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+
+    let mut date = now;
+    let mut number: Option<u32> = None;
+    for word in words {
+        if let Some(count) = parse_number(word) {
+            // Two numbers in a row mean nothing
+            if number.replace(count).is_some() {
+                return None;
+            }
+            continue;
+        }
+
+        match word {
+            "ago" | "now" => {}
+            "yesterday" => date -= Duration::days(1),
+            "today" | "midnight" => date = at_time(date, 0)?,
+            "noon" => date = at_time(date, 12)?,
+            _ => {
+                let count = number.take().unwrap_or(1);
+                if let Some(weekday) = parse_weekday(word) {
+                    // The last such day before today
+                    let days_back = (date.weekday().num_days_from_monday() + 7
+                        - weekday.num_days_from_monday())
+                        % 7;
+                    let days_back = if days_back == 0 { 7 } else { days_back };
+                    let weeks_back = i64::from(count.saturating_sub(1));
+                    date = date.checked_sub_signed(Duration::try_days(
+                        i64::from(days_back) + 7 * weeks_back,
+                    )?)?;
+                } else {
+                    date = go_back(date, word, count)?;
+                }
+            }
+        }
+    }
+
+    // A number without a unit
+    if number.is_some() {
+        return None;
+    }
+    Some(date.to_utc())
+}
+
+fn parse_number(word: &str) -> Option<u32> {
+    let number = match word {
+        "a" | "an" | "one" | "last" => 1,
+        "two" => 2,
+        "three" => 3,
+        "four" => 4,
+        "five" => 5,
+        "six" => 6,
+        "seven" => 7,
+        "eight" => 8,
+        "nine" => 9,
+        "ten" => 10,
+        _ => return word.parse().ok(),
+    };
+    Some(number)
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    let weekday = match word {
+        "monday" | "mon" => Weekday::Mon,
+        "tuesday" | "tue" | "tues" => Weekday::Tue,
+        "wednesday" | "wed" => Weekday::Wed,
+        "thursday" | "thu" | "thurs" => Weekday::Thu,
+        "friday" | "fri" => Weekday::Fri,
+        "saturday" | "sat" => Weekday::Sat,
+        "sunday" | "sun" => Weekday::Sun,
+        _ => return None,
+    };
+    Some(weekday)
+}
+
+/// Goes back `count` of a unit of time such as `week` or `months`.
+fn go_back(date: DateTime<FixedOffset>, unit: &str, count: u32) -> Option<DateTime<FixedOffset>> {
+    let count = i64::from(count);
+    match unit.strip_suffix('s').unwrap_or(unit) {
+        "second" | "sec" => date.checked_sub_signed(Duration::try_seconds(count)?),
+        "minute" | "min" => date.checked_sub_signed(Duration::try_minutes(count)?),
+        "hour" => date.checked_sub_signed(Duration::try_hours(count)?),
+        "day" => date.checked_sub_signed(Duration::try_days(count)?),
+        "week" => date.checked_sub_signed(Duration::try_weeks(count)?),
+        "month" => date.checked_sub_months(Months::new(u32::try_from(count).ok()?)),
+        "year" => date.checked_sub_months(Months::new(u32::try_from(count * 12).ok()?)),
+        _ => None,
+    }
+}
+
+/// The same day at the start of the given hour.
+fn at_time(date: DateTime<FixedOffset>, hour: u32) -> Option<DateTime<FixedOffset>> {
+    date.offset()
+        .from_local_datetime(&date.date_naive().and_hms_opt(hour, 0, 0)?)
+        .single()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Thursday, June 15 2023, at 14:30 two hours ahead of UTC.
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2023-06-15T14:30:00+02:00").unwrap()
+    }
+
+    fn parse(input: &str) -> String {
+        parse_date(input, now()).unwrap().to_rfc3339()
+    }
+
+    #[test]
+    fn test_parse_bare_dates_in_the_given_timezone() {
+        assert_eq!(parse("2023"), "2022-12-31T22:00:00+00:00");
+        assert_eq!(parse("2023-06"), "2023-05-31T22:00:00+00:00");
+        assert_eq!(parse("2023-06-10"), "2023-06-09T22:00:00+00:00");
+        assert_eq!(parse("2023-06-10 08:15"), "2023-06-10T06:15:00+00:00");
+        assert_eq!(parse("2023-06-10T08:15:30"), "2023-06-10T06:15:30+00:00");
+        // An explicit offset wins over the timezone
+        assert_eq!(parse("2023-06-10 08:15 UTC"), "2023-06-10T08:15:00+00:00");
+        assert_eq!(parse("2023-06-10 08:15 -0100"), "2023-06-10T09:15:00+00:00");
+
+        assert!(parse_date("2023-13", now()).is_err());
+        assert!(parse_date("2023-02-30", now()).is_err());
+    }
+
+    #[test]
+    fn test_parse_relative_dates() {
+        assert_eq!(parse("now"), "2023-06-15T12:30:00+00:00");
+        assert_eq!(parse("2 weeks ago"), "2023-06-01T12:30:00+00:00");
+        assert_eq!(parse("3.months"), "2023-03-15T12:30:00+00:00");
+        assert_eq!(parse("1 year, 2 days ago"), "2022-06-13T12:30:00+00:00");
+        assert_eq!(parse("an hour ago"), "2023-06-15T11:30:00+00:00");
+        assert_eq!(parse("ten minutes ago"), "2023-06-15T12:20:00+00:00");
+        assert_eq!(parse("yesterday"), "2023-06-14T12:30:00+00:00");
+        assert_eq!(parse("yesterday noon"), "2023-06-14T10:00:00+00:00");
+        assert_eq!(parse("today"), "2023-06-14T22:00:00+00:00");
+        assert_eq!(parse("last monday"), "2023-06-12T12:30:00+00:00");
+        assert_eq!(parse("last thursday"), "2023-06-08T12:30:00+00:00");
+        assert_eq!(parse("Last Week"), "2023-06-08T12:30:00+00:00");
+
+        for invalid in ["", "not a date", "3", "3 4 days", "2 fortnights ago"] {
+            assert!(parse_date(invalid, now()).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_parse_timezone() {
+        assert_eq!(parse_timezone("UTC").unwrap().local_minus_utc(), 0);
+        assert_eq!(parse_timezone("+0200").unwrap().local_minus_utc(), 7200);
+        assert_eq!(parse_timezone("-05:30").unwrap().local_minus_utc(), -19800);
+
+        assert!(parse_timezone("CEST").is_err());
+        assert!(parse_timezone("+2").is_err());
+    }
+}
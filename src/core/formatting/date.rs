@@ -0,0 +1,182 @@
+use anyhow::Result;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use std::str::FromStr;
+
+/// How dates are shown, like the `--date` option of `git log`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DateFormat {
+    /// How long ago, such as `3 weeks ago`
+    Relative,
+    /// The default format of git, in local time
+    Local,
+    /// `2023-01-15 14:30:22 +0200`
+    Iso,
+    /// `2023-01-15T14:30:22+02:00`
+    IsoStrict,
+    /// `2023-01-15`
+    Short,
+    /// Seconds since the epoch
+    Unix,
+    /// A strftime format (`format:<strftime>`)
+    Format(String),
+}
+
+impl DateFormat {
+    /// Shows a date in its own timezone, or in local time for
+    /// [`DateFormat::Local`].
+    pub fn format(&self, date: &DateTime<FixedOffset>) -> String {
+        match self {
+            DateFormat::Relative => relative(date, &Utc::now()),
+            DateFormat::Local => date
+                .with_timezone(&Local)
+                .format("%a %b %-d %H:%M:%S %Y")
+                .to_string(),
+            DateFormat::Iso => date.format("%Y-%m-%d %H:%M:%S %z").to_string(),
+            DateFormat::IsoStrict => date.format("%Y-%m-%dT%H:%M:%S%:z").to_string(),
+            DateFormat::Short => date.format("%Y-%m-%d").to_string(),
+            DateFormat::Unix => date.timestamp().to_string(),
+            DateFormat::Format(format) => date.format(format).to_string(),
+        }
+    }
+}
+
+impl FromStr for DateFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(format) = s.strip_prefix("format:") {
+            // Formatting would panic on an invalid specifier later on
+            if StrftimeItems::new(format).any(|item| item == Item::Error) {
+                return Err(anyhow::anyhow!("Invalid date format '{}'", format));
+            }
+            return Ok(DateFormat::Format(format.to_string()));
+        }
+
+        match s {
+            "relative" => Ok(DateFormat::Relative),
+            "local" => Ok(DateFormat::Local),
+            "iso" | "iso8601" => Ok(DateFormat::Iso),
+            "iso-strict" | "iso8601-strict" => Ok(DateFormat::IsoStrict),
+            "short" => Ok(DateFormat::Short),
+            "unix" => Ok(DateFormat::Unix),
+            _ => Err(anyhow::anyhow!(
+                "Unknown date format '{}'; expected relative, local, iso, iso-strict, short, unix or format:<strftime>",
+                s
+            )),
+        }
+    }
+}
+
+/// Shows how long before `now` a date is, rounding the way git does.
+fn relative(date: &DateTime<FixedOffset>, now: &DateTime<Utc>) -> String {
+    let seconds = now.timestamp() - date.timestamp();
+    if seconds < 0 {
+        return "in the future".to_string();
+    }
+    if seconds < 90 {
+        return ago(seconds, "second");
+    }
+
+    let minutes = (seconds + 30) / 60;
+    if minutes < 90 {
+        return ago(minutes, "minute");
+    }
+    let hours = (minutes + 30) / 60;
+    if hours < 36 {
+        return ago(hours, "hour");
+    }
+    let days = (hours + 12) / 24;
+    if days < 14 {
+        return ago(days, "day");
+    }
+    if days < 70 {
+        return ago((days + 3) / 7, "week");
+    }
+    if days < 365 {
+        return ago((days + 15) / 30, "month");
+    }
+
+    // Years and months for the first few years
+    if days < 1825 {
+        let total_months = (days * 12 * 2 + 365) / (365 * 2);
+        let (years, months) = (total_months / 12, total_months % 12);
+        if months == 0 {
+            return ago(years, "year");
+        }
+        return format!("{}, {}", plural(years, "year"), ago(months, "month"));
+    }
+    ago((days + 183) / 365, "year")
+}
+
+fn ago(count: i64, unit: &str) -> String {
+    format!("{} ago", plural(count, unit))
+}
+
+fn plural(count: i64, unit: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, unit)
+    } else {
+        format!("{} {}s", count, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse_date_formats() {
+        assert_eq!(
+            "relative".parse::<DateFormat>().unwrap(),
+            DateFormat::Relative
+        );
+        assert_eq!("iso8601".parse::<DateFormat>().unwrap(), DateFormat::Iso);
+        assert_eq!(
+            "format:%d/%m/%Y".parse::<DateFormat>().unwrap(),
+            DateFormat::Format("%d/%m/%Y".to_string())
+        );
+
+        assert!("rfc".parse::<DateFormat>().is_err());
+        assert!("format:%Q".parse::<DateFormat>().is_err());
+    }
+
+    #[test]
+    fn test_format_dates_in_their_timezone() {
+        let date = date("2023-01-15T14:30:22+02:00");
+
+        assert_eq!(DateFormat::Iso.format(&date), "2023-01-15 14:30:22 +0200");
+        assert_eq!(
+            DateFormat::IsoStrict.format(&date),
+            "2023-01-15T14:30:22+02:00"
+        );
+        assert_eq!(DateFormat::Short.format(&date), "2023-01-15");
+        assert_eq!(DateFormat::Unix.format(&date), "1673785822");
+        assert_eq!(
+            DateFormat::Format("%H:%M %z".to_string()).format(&date),
+            "14:30 +0200"
+        );
+    }
+
+    #[test]
+    fn test_relative_dates() {
+        let now = date("2023-06-15T12:00:00Z").to_utc();
+        let relative_to = |s: &str| relative(&date(s), &now);
+
+        assert_eq!(relative_to("2023-06-15T11:59:30Z"), "30 seconds ago");
+        assert_eq!(relative_to("2023-06-15T11:00:00Z"), "60 minutes ago");
+        assert_eq!(relative_to("2023-06-15T09:00:00+02:00"), "5 hours ago");
+        assert_eq!(relative_to("2023-06-14T12:00:00Z"), "24 hours ago");
+        assert_eq!(relative_to("2023-06-10T12:00:00Z"), "5 days ago");
+        assert_eq!(relative_to("2023-05-15T12:00:00Z"), "4 weeks ago");
+        assert_eq!(relative_to("2023-01-15T12:00:00Z"), "5 months ago");
+        assert_eq!(relative_to("2022-06-15T12:00:00Z"), "1 year ago");
+        assert_eq!(relative_to("2021-03-15T12:00:00Z"), "2 years, 3 months ago");
+        assert_eq!(relative_to("2013-06-15T12:00:00Z"), "10 years ago");
+        assert_eq!(relative_to("2023-06-16T12:00:00Z"), "in the future");
+    }
+}
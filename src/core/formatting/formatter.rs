@@ -1,5 +1,60 @@
-use crate::core::line_history::LineHistory;
+use crate::core::line_history::{FileHistory, LineHistory};
 
 pub trait OutputFormatter {
     fn format(&self, history: &LineHistory) -> String;
+
+    /// Formats the history of every line of a file; by default, the history
+    /// of each line one after the other.
+    fn format_file(&self, history: &FileHistory) -> String {
+        history
+            .lines
+            .iter()
+            .map(|line| {
+                self.format(&LineHistory {
+                    file_path: history.file_path.clone(),
+                    line_number: line.line_number,
+                    end_line_number: None,
+                    entries: line.entries.clone(),
+                })
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::line_history::FileLine;
+
+    struct CountingFormatter;
+
+    impl OutputFormatter for CountingFormatter {
+        fn format(&self, history: &LineHistory) -> String {
+            format!(
+                "{}:{} {}",
+                history.file_path,
+                history.line_number,
+                history.entries.len()
+            )
+        }
+    }
+
+    #[test]
+    fn test_format_file_defaults_to_each_line() {
+        let mut history = FileHistory::new("test.rs".to_string());
+        for line_number in 1..=2 {
+            history.add_line(FileLine {
+                line_number,
+                content: String::new(),
+                entries: Vec::new(),
+                latest: None,
+            });
+        }
+
+        assert_eq!(
+            CountingFormatter.format_file(&history),
+            "test.rs:1 0\n\ntest.rs:2 0"
+        );
+    }
 }
@@ -1,3 +1,7 @@
+pub mod date;
 pub mod formatter;
+pub mod person;
 
+pub use date::*;
 pub use formatter::*;
+pub use person::*;
@@ -0,0 +1,22 @@
+/// Shows a name with its email, if there is one.
+pub fn person(name: &str, email: &str) -> String {
+    if email.is_empty() {
+        name.to_string()
+    } else {
+        format!("{} <{}>", name, email)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_person() {
+        assert_eq!(
+            person("Jane", "jane@example.com"),
+            "Jane <jane@example.com>"
+        );
+        assert_eq!(person("Jane", ""), "Jane");
+    }
+}
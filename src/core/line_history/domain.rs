@@ -1,21 +1,149 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LineHistory {
     pub file_path: String,
     pub line_number: u32,
+    /// Last line of the range, for the history of a range of lines
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_line_number: Option<u32>,
     pub entries: Vec<LineEntry>,
 }
 
+/// The history of every line of a file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileHistory {
+    pub file_path: String,
+    /// One history per line of the file, in line order
+    pub lines: Vec<FileLine>,
+}
+
+/// A line of a file along with the changes made to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileLine {
+    pub line_number: u32,
+    /// Text of the line as it is now
+    pub content: String,
+    pub entries: Vec<LineEntry>,
+    /// Index in `entries` of the latest change, following the
+    /// [`CommitOrder`](crate::core::types::CommitOrder) of the history; only
+    /// meaningful alongside `entries`, so it is not serialized
+    #[serde(skip)]
+    pub latest: Option<usize>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LineEntry {
     pub commit_hash: String,
     pub author: String,
-    pub timestamp: DateTime<Utc>,
-    pub message: String,
+    #[serde(default)]
+    pub author_email: String,
+    /// When the change was authored
+    pub timestamp: DateTime<FixedOffset>,
+    /// Who made the commit, such as whoever rebased or merged the change
+    #[serde(default)]
+    pub committer: String,
+    #[serde(default)]
+    pub committer_email: String,
+    /// When the commit was made
+    #[serde(default)]
+    pub committer_timestamp: DateTime<FixedOffset>,
+    /// People credited with `Co-authored-by:` trailers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub co_authors: Vec<Person>,
+    /// First paragraph of the commit message, on a single line
+    pub subject: String,
+    /// Rest of the commit message, without the trailers
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub body: String,
+    /// Trailers from the last paragraph of the commit message
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trailers: Vec<Trailer>,
+    /// Path of the file in this commit, which differs from the history's path
+    /// when the file was renamed or moved later on
+    pub file_path: String,
+    /// Line number in this commit, or in its parent for a deleted line; the
+    /// first line the commit changed for the history of a range
+    #[serde(default)]
+    pub line_number: u32,
     pub content: String,
     pub change_type: ChangeType,
+    /// The hunks of the diff that changed the line, when asked for
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// A name and email, as in a commit signature or trailer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Person {
+    pub name: String,
+    pub email: String,
+}
+
+impl std::fmt::Display for Person {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} <{}>", self.name, self.email)
+    }
+}
+
+/// A `Key: value` line at the end of a commit message, such as
+/// `Signed-off-by` or `Fixes`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Trailer {
+    pub key: String,
+    pub value: String,
+}
+
+impl std::fmt::Display for Trailer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.key, self.value)
+    }
+}
+
+/// A hunk of a diff, as shown by `git log -p`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffHunk {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+impl DiffHunk {
+    /// The `@@ -old_start,old_lines +new_start,new_lines @@` line of the hunk.
+    pub fn header(&self) -> String {
+        format!(
+            "@@ -{},{} +{},{} @@",
+            self.old_start, self.old_lines, self.new_start, self.new_lines
+        )
+    }
+}
+
+/// A line of a [`DiffHunk`], without its line terminator.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DiffLineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+impl DiffLineKind {
+    /// The character a line of this kind starts with in a unified diff.
+    pub fn prefix(self) -> char {
+        match self {
+            DiffLineKind::Context => ' ',
+            DiffLineKind::Added => '+',
+            DiffLineKind::Removed => '-',
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -23,6 +151,10 @@ pub enum ChangeType {
     Created,
     Modified,
     Deleted,
+    /// Moved into the file from another file
+    Moved,
+    /// Copied into the file from another file
+    Copied,
 }
 
 impl std::fmt::Display for ChangeType {
@@ -31,6 +163,8 @@ impl std::fmt::Display for ChangeType {
             ChangeType::Created => write!(f, "Created"),
             ChangeType::Modified => write!(f, "Modified"),
             ChangeType::Deleted => write!(f, "Deleted"),
+            ChangeType::Moved => write!(f, "Moved"),
+            ChangeType::Copied => write!(f, "Copied"),
         }
     }
 }
@@ -40,10 +174,27 @@ impl LineHistory {
         Self {
             file_path,
             line_number,
+            end_line_number: None,
             entries: Vec::new(),
         }
     }
 
+    /// Creates the history of the lines from `start` to `end`.
+    pub fn for_range(file_path: String, start: u32, end: u32) -> Self {
+        Self {
+            end_line_number: (end != start).then_some(end),
+            ..Self::new(file_path, start)
+        }
+    }
+
+    /// The line, or range of lines, as `start` or `start-end`.
+    pub fn span(&self) -> String {
+        match self.end_line_number {
+            Some(end) => format!("{}-{}", self.line_number, end),
+            None => self.line_number.to_string(),
+        }
+    }
+
     pub fn add_entry(&mut self, entry: LineEntry) {
         self.entries.push(entry);
     }
@@ -53,10 +204,79 @@ impl LineHistory {
     }
 }
 
+impl LineEntry {
+    /// The commit message put back together from its subject, body and
+    /// trailers.
+    pub fn full_message(&self) -> String {
+        let mut message = self.subject.clone();
+        if !self.body.is_empty() {
+            message.push_str("\n\n");
+            message.push_str(&self.body);
+        }
+        if !self.trailers.is_empty() {
+            message.push('\n');
+            for trailer in &self.trailers {
+                message.push_str(&format!("\n{}", trailer));
+            }
+        }
+        message
+    }
+}
+
+/// An entry with nothing filled in, so that tests only spell out the fields
+/// they look at.
+#[cfg(test)]
+impl Default for LineEntry {
+    fn default() -> Self {
+        Self {
+            commit_hash: String::new(),
+            author: String::new(),
+            author_email: String::new(),
+            timestamp: Default::default(),
+            committer: String::new(),
+            committer_email: String::new(),
+            committer_timestamp: Default::default(),
+            co_authors: Vec::new(),
+            subject: String::new(),
+            body: String::new(),
+            trailers: Vec::new(),
+            file_path: String::new(),
+            line_number: 0,
+            content: String::new(),
+            change_type: ChangeType::Modified,
+            hunks: Vec::new(),
+        }
+    }
+}
+
+impl FileHistory {
+    pub fn new(file_path: String) -> Self {
+        Self {
+            file_path,
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn add_line(&mut self, line: FileLine) {
+        self.lines.push(line);
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+}
+
+impl FileLine {
+    /// The most recent change to the line, whatever the order of the entries.
+    pub fn latest_entry(&self) -> Option<&LineEntry> {
+        self.latest.and_then(|index| self.entries.get(index))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::TimeZone;
+    use chrono::{TimeZone, Utc};
 
     #[test]
     fn test_line_history_creation() {
@@ -67,16 +287,31 @@ mod tests {
         assert_eq!(history.entry_count(), 0);
     }
 
+    #[test]
+    fn test_line_history_for_range() {
+        let history = LineHistory::for_range("test.rs".to_string(), 10, 40);
+        assert_eq!(history.line_number, 10);
+        assert_eq!(history.end_line_number, Some(40));
+        assert_eq!(history.span(), "10-40");
+
+        let history = LineHistory::for_range("test.rs".to_string(), 7, 7);
+        assert_eq!(history.end_line_number, None);
+        assert_eq!(history.span(), "7");
+    }
+
     #[test]
     fn test_add_entry() {
         let mut history = LineHistory::new("test.rs".to_string(), 42);
         let entry = LineEntry {
             commit_hash: "abc123".to_string(),
             author: "John Doe".to_string(),
-            timestamp: Utc.timestamp_opt(1234567890, 0).unwrap(),
-            message: "Initial commit".to_string(),
+            timestamp: Utc.timestamp_opt(1234567890, 0).unwrap().fixed_offset(),
+            subject: "Initial commit".to_string(),
+            file_path: "test.rs".to_string(),
+            line_number: 42,
             content: "println!(\"Hello, world!\");".to_string(),
             change_type: ChangeType::Created,
+            ..Default::default()
         };
 
         history.add_entry(entry.clone());
@@ -90,10 +325,13 @@ mod tests {
         let entry = LineEntry {
             commit_hash: "abc123".to_string(),
             author: "John Doe".to_string(),
-            timestamp: Utc.timestamp_opt(1234567890, 0).unwrap(),
-            message: "Initial commit".to_string(),
+            timestamp: Utc.timestamp_opt(1234567890, 0).unwrap().fixed_offset(),
+            subject: "Initial commit".to_string(),
+            file_path: "test.rs".to_string(),
+            line_number: 1,
             content: "println!(\"Hello, world!\");".to_string(),
             change_type: ChangeType::Created,
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&entry).unwrap();
@@ -101,4 +339,68 @@ mod tests {
 
         assert_eq!(entry, deserialized);
     }
+
+    #[test]
+    fn test_line_entry_full_message() {
+        let mut entry = LineEntry {
+            commit_hash: "abc123".to_string(),
+            author: "John Doe".to_string(),
+            timestamp: Utc.timestamp_opt(1234567890, 0).unwrap().fixed_offset(),
+            subject: "Fix parser".to_string(),
+            file_path: "test.rs".to_string(),
+            line_number: 1,
+            content: String::new(),
+            change_type: ChangeType::Modified,
+            ..Default::default()
+        };
+        assert_eq!(entry.full_message(), "Fix parser");
+
+        entry.trailers = vec![Trailer {
+            key: "Fixes".to_string(),
+            value: "#12".to_string(),
+        }];
+        assert_eq!(entry.full_message(), "Fix parser\n\nFixes: #12");
+
+        entry.body = "It broke.\n\nTwice.".to_string();
+        assert_eq!(
+            entry.full_message(),
+            "Fix parser\n\nIt broke.\n\nTwice.\n\nFixes: #12"
+        );
+    }
+
+    #[test]
+    fn test_file_history() {
+        let mut history = FileHistory::new("test.rs".to_string());
+        let entry = |hash: &str, seconds| LineEntry {
+            commit_hash: hash.to_string(),
+            author: "John Doe".to_string(),
+            timestamp: Utc.timestamp_opt(seconds, 0).unwrap().fixed_offset(),
+            subject: "Change".to_string(),
+            file_path: "test.rs".to_string(),
+            line_number: 1,
+            content: "fn main() {}".to_string(),
+            change_type: ChangeType::Modified,
+            ..Default::default()
+        };
+
+        history.add_line(FileLine {
+            line_number: 1,
+            content: "fn main() {}".to_string(),
+            entries: vec![entry("abc123", 200), entry("def456", 100)],
+            latest: Some(1),
+        });
+        history.add_line(FileLine {
+            line_number: 2,
+            content: String::new(),
+            entries: Vec::new(),
+            latest: None,
+        });
+
+        assert_eq!(history.line_count(), 2);
+        assert_eq!(
+            history.lines[0].latest_entry().unwrap().commit_hash,
+            "def456"
+        );
+        assert!(history.lines[1].latest_entry().is_none());
+    }
 }
@@ -1,7 +1,11 @@
 pub mod domain;
+pub mod options;
 pub mod provider;
+pub mod selector;
 pub mod use_case;
 
 pub use domain::*;
+pub use options::*;
 pub use provider::*;
+pub use selector::*;
 pub use use_case::*;
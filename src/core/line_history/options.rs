@@ -0,0 +1,29 @@
+use crate::core::types::{CommitOrder, SortOrder};
+
+/// Options controlling which changes end up in a line history.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryOptions {
+    pub sort_order: SortOrder,
+    /// How commits are ordered before `sort_order` is applied
+    pub order: CommitOrder,
+    /// Revisions whose changes are passed through to the change before them
+    pub ignore_revs: Vec<String>,
+    /// Files listing more revisions to leave out, one per line
+    pub ignore_revs_files: Vec<String>,
+    /// Skip the ignore-revs files configured with `blame.ignoreRevsFile`
+    pub no_ignore_revs_file: bool,
+    /// Only include commits more recent than this date
+    pub since: Option<String>,
+    /// Only include commits older than this date
+    pub until: Option<String>,
+    /// Timezone bare `since` and `until` dates are in, as an offset such as
+    /// `+0200` or `UTC`; local time otherwise
+    pub timezone: Option<String>,
+    /// Leave out merge commits, while still following lines through them
+    pub no_merges: bool,
+    /// Stop looking for changes once this many have been found
+    pub limit: Option<usize>,
+    /// Revision or range (`A..B`) to start from instead of HEAD; line numbers
+    /// refer to the file as it is in that revision
+    pub rev: Option<String>,
+}
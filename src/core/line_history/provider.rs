@@ -1,5 +1,6 @@
-use crate::core::line_history::domain::LineHistory;
-use crate::core::types::SortOrder;
+use crate::core::line_history::domain::{FileHistory, LineHistory};
+use crate::core::line_history::options::HistoryOptions;
+use crate::core::line_history::selector::LineSelector;
 use anyhow::Result;
 
 pub trait LineHistoryProvider {
@@ -7,9 +8,19 @@ pub trait LineHistoryProvider {
         &self,
         file_path: &str,
         line_number: u32,
-        sort_order: SortOrder,
-        ignore_revs: &[String],
-        since: Option<&str>,
-        until: Option<&str>,
+        options: &HistoryOptions,
     ) -> Result<LineHistory>;
+
+    /// Returns the commits that changed any line of a range, resolving the
+    /// range against the file at the starting revision.
+    fn get_line_range_history(
+        &self,
+        file_path: &str,
+        selector: &LineSelector,
+        options: &HistoryOptions,
+    ) -> Result<LineHistory>;
+
+    /// Returns the history of every line of a file, found in a single walk
+    /// over the commits.
+    fn get_file_history(&self, file_path: &str, options: &HistoryOptions) -> Result<FileHistory>;
 }
@@ -0,0 +1,248 @@
+use anyhow::Result;
+use regex::Regex;
+use std::str::FromStr;
+
+/// A range of lines given the way `git log -L` takes them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LineSelector {
+    /// From `start` to `end`, or only the `start` line without an end
+    Range {
+        start: RangeBound,
+        end: Option<RangeBound>,
+    },
+    /// The function whose definition matches the pattern (`:funcname`)
+    Function(String),
+}
+
+/// One end of a [`LineSelector::Range`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum RangeBound {
+    /// An absolute line number
+    Line(u32),
+    /// A number of lines counted from the start (`+n`), the start included
+    Count(u32),
+    /// The first line matching a regular expression (`/regex/`); for the end,
+    /// the first match after the start
+    Pattern(String),
+}
+
+impl LineSelector {
+    /// Resolves the selector against the content of a file into the 1-based
+    /// first and last lines of the range.
+    ///
+    /// Line numbers are not checked against the length of the file.
+    pub fn resolve(&self, content: &str) -> Result<(u32, u32)> {
+        let lines: Vec<&str> = content.lines().collect();
+
+        match self {
+            LineSelector::Range { start, end } => {
+                let start = match start {
+                    RangeBound::Line(line) => *line,
+                    RangeBound::Pattern(pattern) => find_matching_line(&lines, pattern, 0)?,
+                    RangeBound::Count(_) => {
+                        return Err(anyhow::anyhow!("The start of a range must be a line"));
+                    }
+                };
+                let end = match end {
+                    None => start,
+                    Some(RangeBound::Line(line)) => *line,
+                    Some(RangeBound::Count(0)) => {
+                        return Err(anyhow::anyhow!("A range must span at least one line"));
+                    }
+                    Some(RangeBound::Count(count)) => start.saturating_add(count - 1),
+                    Some(RangeBound::Pattern(pattern)) => {
+                        find_matching_line(&lines, pattern, start as usize)?
+                    }
+                };
+                Ok((start.min(end), start.max(end)))
+            }
+            LineSelector::Function(pattern) => find_function(&lines, pattern),
+        }
+    }
+}
+
+impl FromStr for LineSelector {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(pattern) = s.strip_prefix(':') {
+            if pattern.is_empty() {
+                return Err(anyhow::anyhow!("Missing function name in '{}'", s));
+            }
+            return Ok(LineSelector::Function(pattern.to_string()));
+        }
+
+        let (start, end) = split_range(s)?;
+        let start = parse_bound(start, s)?;
+        if matches!(start, RangeBound::Count(_)) {
+            return Err(anyhow::anyhow!("The start of range '{}' must be a line", s));
+        }
+        let end = end.map(|end| parse_bound(end, s)).transpose()?;
+
+        Ok(LineSelector::Range { start, end })
+    }
+}
+
+/// Splits `start,end` at the comma that is not part of a `/regex/`.
+fn split_range(s: &str) -> Result<(&str, Option<&str>)> {
+    let start_end = match s.strip_prefix('/') {
+        Some(rest) => {
+            let close = rest
+                .find('/')
+                .ok_or_else(|| anyhow::anyhow!("Unterminated pattern in range '{}'", s))?;
+            close + 2
+        }
+        None => s.find(',').unwrap_or(s.len()),
+    };
+
+    let (start, rest) = s.split_at(start_end);
+    match rest.strip_prefix(',') {
+        Some(end) => Ok((start, Some(end))),
+        None if rest.is_empty() => Ok((start, None)),
+        None => Err(anyhow::anyhow!("Invalid range '{}'", s)),
+    }
+}
+
+fn parse_bound(bound: &str, range: &str) -> Result<RangeBound> {
+    if let Some(pattern) = bound
+        .strip_prefix('/')
+        .and_then(|bound| bound.strip_suffix('/'))
+    {
+        Regex::new(pattern)
+            .map_err(|e| anyhow::anyhow!("Invalid pattern in range '{}': {}", range, e))?;
+        return Ok(RangeBound::Pattern(pattern.to_string()));
+    }
+
+    if let Some(count) = bound.strip_prefix('+') {
+        let count = count
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid line count in range '{}'", range))?;
+        return Ok(RangeBound::Count(count));
+    }
+
+    bound
+        .parse()
+        .map(RangeBound::Line)
+        .map_err(|_| anyhow::anyhow!("Invalid line in range '{}'", range))
+}
+
+/// Finds the first line after the first `skip` lines matching `pattern`.
+fn find_matching_line(lines: &[&str], pattern: &str, skip: usize) -> Result<u32> {
+    let regex = Regex::new(pattern)?;
+    lines
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .find(|(_, line)| regex.is_match(line))
+        .map(|(index, _)| index as u32 + 1)
+        .ok_or_else(|| anyhow::anyhow!("No line matches /{}/", pattern))
+}
+
+/// Finds the definition of the function matching `pattern` and the lines of
+/// its body.
+///
+/// Like git's default funcname matching, a definition is a line starting with
+/// a letter, `_` or `$` (after any indentation). The body runs until the next
+/// line that is not indented deeper, keeping a closing bracket on that line.
+fn find_function(lines: &[&str], pattern: &str) -> Result<(u32, u32)> {
+    let regex = Regex::new(pattern)
+        .map_err(|e| anyhow::anyhow!("Invalid function pattern '{}': {}", pattern, e))?;
+
+    let start = lines
+        .iter()
+        .position(|line| {
+            line.trim_start()
+                .starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '$')
+                && regex.is_match(line)
+        })
+        .ok_or_else(|| anyhow::anyhow!("No function matches :{}", pattern))?;
+
+    let depth = indentation(lines[start]);
+    let mut end = start;
+    for (index, line) in lines.iter().enumerate().skip(start + 1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if indentation(line) > depth {
+            end = index;
+            continue;
+        }
+        if line.trim_start().starts_with(['}', ')', ']']) {
+            end = index;
+        }
+        break;
+    }
+
+    Ok((start as u32 + 1, end as u32 + 1))
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "use std::fmt;\n\nfn parse() {\n    let a = 1;\n\n    a\n}\n\nfn main() {\n    parse();\n}\n";
+
+    #[test]
+    fn test_parse_line_selectors() {
+        assert_eq!(
+            "10,40".parse::<LineSelector>().unwrap(),
+            LineSelector::Range {
+                start: RangeBound::Line(10),
+                end: Some(RangeBound::Line(40)),
+            }
+        );
+        assert_eq!(
+            "10,+5".parse::<LineSelector>().unwrap(),
+            LineSelector::Range {
+                start: RangeBound::Line(10),
+                end: Some(RangeBound::Count(5)),
+            }
+        );
+        assert_eq!(
+            "/fn, main/,+2".parse::<LineSelector>().unwrap(),
+            LineSelector::Range {
+                start: RangeBound::Pattern("fn, main".to_string()),
+                end: Some(RangeBound::Count(2)),
+            }
+        );
+        assert_eq!(
+            ":parse".parse::<LineSelector>().unwrap(),
+            LineSelector::Function("parse".to_string())
+        );
+
+        for invalid in ["", "a,b", "+3", "10,", ":", "/(/", "/open", "/a/b"] {
+            assert!(invalid.parse::<LineSelector>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_resolve_ranges() {
+        let resolve = |selector: &str| selector.parse::<LineSelector>().unwrap().resolve(SOURCE);
+
+        assert_eq!(resolve("3,7").unwrap(), (3, 7));
+        assert_eq!(resolve("7,3").unwrap(), (3, 7));
+        assert_eq!(resolve("3,+2").unwrap(), (3, 4));
+        assert_eq!(resolve("5").unwrap(), (5, 5));
+        assert_eq!(resolve("/fn main/,/}/").unwrap(), (9, 11));
+        assert_eq!(resolve("/parse/").unwrap(), (3, 3));
+        assert!(resolve("/missing/").is_err());
+        assert!(resolve("3,+0").is_err());
+    }
+
+    #[test]
+    fn test_resolve_functions() {
+        let resolve = |selector: &str| selector.parse::<LineSelector>().unwrap().resolve(SOURCE);
+
+        assert_eq!(resolve(":parse").unwrap(), (3, 7));
+        assert_eq!(resolve(":main").unwrap(), (9, 11));
+        assert!(resolve(":missing").is_err());
+
+        let python = "def run():\n    x = 1\n    return x\n\ndef other():\n    pass\n";
+        let selector: LineSelector = ":run".parse().unwrap();
+        assert_eq!(selector.resolve(python).unwrap(), (1, 3));
+    }
+}
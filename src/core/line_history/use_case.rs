@@ -1,5 +1,6 @@
-use crate::core::line_history::{LineHistory, LineHistoryProvider};
-use crate::core::types::SortOrder;
+use crate::core::line_history::{
+    FileHistory, HistoryOptions, LineHistory, LineHistoryProvider, LineSelector,
+};
 use anyhow::Result;
 
 pub struct LineHistoryUseCase<P: LineHistoryProvider> {
@@ -15,26 +16,36 @@ impl<P: LineHistoryProvider> LineHistoryUseCase<P> {
         &self,
         file_path: &str,
         line_number: u32,
-        sort_order: SortOrder,
-        ignore_revs: &[String],
-        since: Option<&str>,
-        until: Option<&str>,
+        options: &HistoryOptions,
     ) -> Result<LineHistory> {
-        self.provider.get_line_history(
-            file_path,
-            line_number,
-            sort_order,
-            ignore_revs,
-            since,
-            until,
-        )
+        self.provider
+            .get_line_history(file_path, line_number, options)
+    }
+
+    pub fn get_line_range_history(
+        &self,
+        file_path: &str,
+        selector: &LineSelector,
+        options: &HistoryOptions,
+    ) -> Result<LineHistory> {
+        self.provider
+            .get_line_range_history(file_path, selector, options)
+    }
+
+    pub fn get_file_history(
+        &self,
+        file_path: &str,
+        options: &HistoryOptions,
+    ) -> Result<FileHistory> {
+        self.provider.get_file_history(file_path, options)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::line_history::{ChangeType, LineEntry};
+    use crate::core::line_history::{ChangeType, FileLine, LineEntry};
+    use crate::core::types::SortOrder;
     use chrono::{TimeZone, Utc};
 
     struct EmptyProvider;
@@ -44,13 +55,27 @@ mod tests {
             &self,
             _file_path: &str,
             _line_number: u32,
-            _sort_order: SortOrder,
-            _ignore_revs: &[String],
-            _since: Option<&str>,
-            _until: Option<&str>,
+            _options: &HistoryOptions,
         ) -> Result<LineHistory> {
             Ok(LineHistory::new("test.rs".to_string(), 42))
         }
+
+        fn get_line_range_history(
+            &self,
+            _file_path: &str,
+            _selector: &LineSelector,
+            _options: &HistoryOptions,
+        ) -> Result<LineHistory> {
+            Ok(LineHistory::for_range("test.rs".to_string(), 10, 40))
+        }
+
+        fn get_file_history(
+            &self,
+            _file_path: &str,
+            _options: &HistoryOptions,
+        ) -> Result<FileHistory> {
+            Ok(FileHistory::new("test.rs".to_string()))
+        }
     }
 
     struct PopulatedProvider;
@@ -60,19 +85,46 @@ mod tests {
             &self,
             _file_path: &str,
             _line_number: u32,
-            _sort_order: SortOrder,
-            _ignore_revs: &[String],
-            _since: Option<&str>,
-            _until: Option<&str>,
+            _options: &HistoryOptions,
         ) -> Result<LineHistory> {
             let mut history = LineHistory::new("test.rs".to_string(), 42);
             history.add_entry(LineEntry {
                 commit_hash: "abc123".to_string(),
                 author: "John Doe".to_string(),
-                timestamp: Utc.timestamp_opt(1234567890, 0).unwrap(),
-                message: "Initial commit".to_string(),
+                timestamp: Utc.timestamp_opt(1234567890, 0).unwrap().fixed_offset(),
+                subject: "Initial commit".to_string(),
+                file_path: "test.rs".to_string(),
+                line_number: 42,
                 content: "println!(\"Hello, world!\");".to_string(),
                 change_type: ChangeType::Created,
+                ..Default::default()
+            });
+            Ok(history)
+        }
+
+        fn get_line_range_history(
+            &self,
+            file_path: &str,
+            _selector: &LineSelector,
+            options: &HistoryOptions,
+        ) -> Result<LineHistory> {
+            let mut history = self.get_line_history(file_path, 42, options)?;
+            history.end_line_number = Some(44);
+            Ok(history)
+        }
+
+        fn get_file_history(
+            &self,
+            file_path: &str,
+            options: &HistoryOptions,
+        ) -> Result<FileHistory> {
+            let line = self.get_line_history(file_path, 1, options)?;
+            let mut history = FileHistory::new(line.file_path);
+            history.add_line(FileLine {
+                line_number: 1,
+                content: "println!(\"Hello, world!\");".to_string(),
+                entries: line.entries,
+                latest: None,
             });
             Ok(history)
         }
@@ -83,7 +135,7 @@ mod tests {
         let provider = EmptyProvider;
         let use_case = LineHistoryUseCase::new(provider);
         let result = use_case
-            .get_line_history("test.rs", 42, SortOrder::Asc, &[], None, None)
+            .get_line_history("test.rs", 42, &HistoryOptions::default())
             .unwrap();
 
         assert_eq!(result.file_path, "test.rs");
@@ -96,7 +148,7 @@ mod tests {
         let provider = PopulatedProvider;
         let use_case = LineHistoryUseCase::new(provider);
         let result = use_case
-            .get_line_history("test.rs", 42, SortOrder::Asc, &[], None, None)
+            .get_line_history("test.rs", 42, &HistoryOptions::default())
             .unwrap();
 
         assert_eq!(result.file_path, "test.rs");
@@ -110,10 +162,17 @@ mod tests {
         let provider = PopulatedProvider;
         let use_case = LineHistoryUseCase::new(provider);
         let result_asc = use_case
-            .get_line_history("test.rs", 42, SortOrder::Asc, &[], None, None)
+            .get_line_history("test.rs", 42, &HistoryOptions::default())
             .unwrap();
         let result_desc = use_case
-            .get_line_history("test.rs", 42, SortOrder::Desc, &[], None, None)
+            .get_line_history(
+                "test.rs",
+                42,
+                &HistoryOptions {
+                    sort_order: SortOrder::Desc,
+                    ..Default::default()
+                },
+            )
             .unwrap();
 
         assert_eq!(result_asc.file_path, "test.rs");
@@ -128,7 +187,14 @@ mod tests {
         let use_case = LineHistoryUseCase::new(provider);
         let ignore_revs = vec!["abc123".to_string()];
         let result = use_case
-            .get_line_history("test.rs", 42, SortOrder::Asc, &ignore_revs, None, None)
+            .get_line_history(
+                "test.rs",
+                42,
+                &HistoryOptions {
+                    ignore_revs: ignore_revs.clone(),
+                    ..Default::default()
+                },
+            )
             .unwrap();
 
         assert_eq!(result.file_path, "test.rs");
@@ -141,7 +207,14 @@ mod tests {
         let provider = PopulatedProvider;
         let use_case = LineHistoryUseCase::new(provider);
         let result = use_case
-            .get_line_history("test.rs", 42, SortOrder::Asc, &[], Some("2023-01-01"), None)
+            .get_line_history(
+                "test.rs",
+                42,
+                &HistoryOptions {
+                    since: Some("2023-01-01".to_string()),
+                    ..Default::default()
+                },
+            )
             .unwrap();
 
         assert_eq!(result.file_path, "test.rs");
@@ -154,7 +227,14 @@ mod tests {
         let provider = PopulatedProvider;
         let use_case = LineHistoryUseCase::new(provider);
         let result = use_case
-            .get_line_history("test.rs", 42, SortOrder::Asc, &[], None, Some("2023-12-31"))
+            .get_line_history(
+                "test.rs",
+                42,
+                &HistoryOptions {
+                    until: Some("2023-12-31".to_string()),
+                    ..Default::default()
+                },
+            )
             .unwrap();
 
         assert_eq!(result.file_path, "test.rs");
@@ -170,10 +250,11 @@ mod tests {
             .get_line_history(
                 "test.rs",
                 42,
-                SortOrder::Asc,
-                &[],
-                Some("2023-01-01"),
-                Some("2023-12-31"),
+                &HistoryOptions {
+                    since: Some("2023-01-01".to_string()),
+                    until: Some("2023-12-31".to_string()),
+                    ..Default::default()
+                },
             )
             .unwrap();
 
@@ -181,4 +262,54 @@ mod tests {
         assert_eq!(result.line_number, 42);
         // Note: PopulatedProvider doesn't actually filter, this just tests the parameter passing
     }
+
+    #[test]
+    fn test_use_case_with_limit_parameter() {
+        let provider = PopulatedProvider;
+        let use_case = LineHistoryUseCase::new(provider);
+        let options = HistoryOptions {
+            limit: Some(1),
+            ..Default::default()
+        };
+        let result = use_case.get_line_history("test.rs", 42, &options).unwrap();
+
+        assert_eq!(result.file_path, "test.rs");
+        assert_eq!(result.line_number, 42);
+        // Note: PopulatedProvider doesn't actually limit, this just tests the parameter passing
+    }
+
+    #[test]
+    fn test_use_case_with_line_range() {
+        let use_case = LineHistoryUseCase::new(EmptyProvider);
+        let selector: LineSelector = "10,40".parse().unwrap();
+        let result = use_case
+            .get_line_range_history("test.rs", &selector, &HistoryOptions::default())
+            .unwrap();
+
+        assert_eq!(result.line_number, 10);
+        assert_eq!(result.end_line_number, Some(40));
+
+        let use_case = LineHistoryUseCase::new(PopulatedProvider);
+        let result = use_case
+            .get_line_range_history("test.rs", &selector, &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(result.span(), "42-44");
+        assert_eq!(result.entries.len(), 1);
+    }
+
+    #[test]
+    fn test_use_case_with_file_history() {
+        let use_case = LineHistoryUseCase::new(EmptyProvider);
+        let result = use_case
+            .get_file_history("test.rs", &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(result.line_count(), 0);
+
+        let use_case = LineHistoryUseCase::new(PopulatedProvider);
+        let result = use_case
+            .get_file_history("test.rs", &HistoryOptions::default())
+            .unwrap();
+        assert_eq!(result.file_path, "test.rs");
+        assert_eq!(result.lines[0].entries.len(), 1);
+    }
 }
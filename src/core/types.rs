@@ -1,7 +1,20 @@
 use clap::ValueEnum;
 
-#[derive(Clone, Debug, PartialEq, ValueEnum)]
+#[derive(Clone, Debug, Default, PartialEq, ValueEnum)]
 pub enum SortOrder {
+    #[default]
     Asc,
     Desc,
 }
+
+/// What "older" means when ordering the commits of a history.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum CommitOrder {
+    /// The order of the commits in the ancestry, whatever their dates
+    #[default]
+    Topo,
+    /// The date the changes were originally authored
+    AuthorDate,
+    /// The date the commits were made, which rebases and cherry-picks update
+    CommitterDate,
+}
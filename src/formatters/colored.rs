@@ -1,12 +1,43 @@
-use crate::core::formatting::OutputFormatter;
-use crate::core::line_history::LineHistory;
+use crate::core::formatting::{DateFormat, OutputFormatter, person};
+use crate::core::line_history::{DiffLineKind, FileHistory, LineHistory};
+use chrono::{DateTime, FixedOffset};
 use colored::Colorize;
 
-pub struct ColoredFormatter;
+pub struct ColoredFormatter {
+    full_message: bool,
+    date_format: Option<DateFormat>,
+}
 
 impl ColoredFormatter {
     pub fn new() -> Self {
-        Self
+        Self {
+            full_message: false,
+            date_format: None,
+        }
+    }
+
+    /// Shows dates in the given format instead of ISO dates for histories and
+    /// short dates for files.
+    pub fn with_date_format(mut self, date_format: Option<DateFormat>) -> Self {
+        self.date_format = date_format;
+        self
+    }
+
+    /// Shows the whole commit message of each entry instead of its subject.
+    pub fn with_full_message(mut self, enabled: bool) -> Self {
+        self.full_message = enabled;
+        self
+    }
+
+    /// Shows a date in the chosen format, or in `default`.
+    fn date(&self, date: &DateTime<FixedOffset>, default: &DateFormat) -> String {
+        self.date_format.as_ref().unwrap_or(default).format(date)
+    }
+}
+
+impl Default for ColoredFormatter {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -17,7 +48,7 @@ impl OutputFormatter for ColoredFormatter {
         output.push_str(&format!(
             "{}:{}\n",
             history.file_path.cyan(),
-            history.line_number.to_string().yellow()
+            history.span().yellow()
         ));
 
         if history.entries.is_empty() {
@@ -37,31 +68,147 @@ impl OutputFormatter for ColoredFormatter {
             };
 
             output.push_str(&format!(
-                "{} {} {} {}\n{}",
+                "{} {} {} {}",
                 short_hash.bright_green(),
-                entry.author.blue(),
-                entry
-                    .timestamp
-                    .format("%Y-%m-%d %H:%M:%S")
-                    .to_string()
-                    .white(),
-                format!("({})", entry.change_type).purple(),
-                entry.message.white()
+                person(&entry.author, &entry.author_email).blue(),
+                self.date(&entry.timestamp, &DateFormat::Iso).white(),
+                format!("({})", entry.change_type).purple()
             ));
 
-            if !entry.content.is_empty() {
-                output.push_str(&format!("\n  {}", entry.content.bright_white()));
+            // The line was somewhere else in this commit
+            if entry.file_path != history.file_path || entry.line_number != history.line_number {
+                output.push_str(&format!(
+                    " {}",
+                    format!("{}:{}", entry.file_path, entry.line_number).cyan()
+                ));
+            }
+
+            // Someone else committed the change, or committed it later on
+            if entry.committer != entry.author
+                || entry.committer_email != entry.author_email
+                || entry.committer_timestamp != entry.timestamp
+            {
+                output.push_str(&format!(
+                    "\n{} {} {}",
+                    "Committed by".dimmed(),
+                    person(&entry.committer, &entry.committer_email).blue(),
+                    self.date(&entry.committer_timestamp, &DateFormat::Iso)
+                        .white()
+                ));
+            }
+            for co_author in &entry.co_authors {
+                output.push_str(&format!(
+                    "\n{} {}",
+                    "Co-authored by".dimmed(),
+                    person(&co_author.name, &co_author.email).blue()
+                ));
+            }
+
+            if self.full_message {
+                for line in entry.full_message().lines() {
+                    output.push_str(&format!("\n{}", line.white()));
+                }
+            } else {
+                output.push_str(&format!("\n{}", entry.subject.white()));
+            }
+
+            // Entries of a range hold one line per changed line
+            for line in entry.content.lines().filter(|line| !line.is_empty()) {
+                output.push_str(&format!("\n  {}", line.bright_white()));
+            }
+
+            for hunk in &entry.hunks {
+                output.push_str(&format!("\n{}", hunk.header().cyan()));
+                for line in &hunk.lines {
+                    let text = format!("{}{}", line.kind.prefix(), line.content);
+                    let text = match line.kind {
+                        DiffLineKind::Context => text.normal(),
+                        DiffLineKind::Added => text.green(),
+                        DiffLineKind::Removed => text.red(),
+                    };
+                    output.push_str(&format!("\n{}", text));
+                }
             }
         }
 
         output
     }
+
+    fn format_file(&self, history: &FileHistory) -> String {
+        let mut output = format!("{}\n", history.file_path.cyan());
+
+        if history.lines.is_empty() {
+            output.push_str(&"No lines found".dimmed().to_string());
+            return output;
+        }
+
+        // Columns are padded to line up like `git blame`
+        let author_width = history
+            .lines
+            .iter()
+            .filter_map(|line| line.latest_entry())
+            .map(|entry| entry.author.chars().count())
+            .max()
+            .unwrap_or(0);
+        let count_width = history
+            .lines
+            .iter()
+            .map(|line| line.entries.len().to_string().len())
+            .max()
+            .unwrap_or(1);
+        let line_width = history
+            .lines
+            .last()
+            .map_or(1, |line| line.line_number.to_string().len());
+        let dates: Vec<String> = history
+            .lines
+            .iter()
+            .map(|line| {
+                line.latest_entry()
+                    .map(|entry| self.date(&entry.timestamp, &DateFormat::Short))
+                    .unwrap_or_default()
+            })
+            .collect();
+        let date_width = dates
+            .iter()
+            .map(|date| date.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        for (i, (line, date)) in history.lines.iter().zip(dates).enumerate() {
+            if i > 0 {
+                output.push('\n');
+            }
+
+            let (short_hash, author) = match line.latest_entry() {
+                Some(entry) => (
+                    entry.commit_hash.chars().take(8).collect(),
+                    entry.author.clone(),
+                ),
+                None => ("-".repeat(8), String::new()),
+            };
+
+            output.push_str(&format!(
+                "{} {} {} {} {} {}",
+                short_hash.bright_green(),
+                format!("{:<author_width$}", author).blue(),
+                format!("{:<date_width$}", date).white(),
+                format!("{:>count_width$}", line.entries.len()).purple(),
+                format!("{:>line_width$})", line.line_number).yellow(),
+                line.content.bright_white()
+            ));
+        }
+
+        output
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::line_history::{ChangeType, LineEntry};
+    use crate::core::line_history::{
+        ChangeType, DiffHunk, DiffLine, FileLine, LineEntry, Person, Trailer,
+    };
     use chrono::{TimeZone, Utc};
 
     #[test]
@@ -89,10 +236,13 @@ mod tests {
         history.add_entry(LineEntry {
             commit_hash: "abc123456789".to_string(),
             author: "John Doe".to_string(),
-            timestamp: Utc.timestamp_opt(1234567890, 0).unwrap(),
-            message: "Initial commit".to_string(),
+            timestamp: Utc.timestamp_opt(1234567890, 0).unwrap().fixed_offset(),
+            subject: "Initial commit".to_string(),
+            file_path: "test.rs".to_string(),
+            line_number: 42,
             content: "println!(\"Hello, world!\");".to_string(),
             change_type: ChangeType::Created,
+            ..Default::default()
         });
 
         let result = formatter.format(&history);
@@ -119,19 +269,25 @@ mod tests {
         history.add_entry(LineEntry {
             commit_hash: "abc123".to_string(),
             author: "John Doe".to_string(),
-            timestamp: Utc.timestamp_opt(1234567890, 0).unwrap(),
-            message: "Initial commit".to_string(),
+            timestamp: Utc.timestamp_opt(1234567890, 0).unwrap().fixed_offset(),
+            subject: "Initial commit".to_string(),
+            file_path: "test.rs".to_string(),
+            line_number: 42,
             content: "old content".to_string(),
             change_type: ChangeType::Created,
+            ..Default::default()
         });
 
         history.add_entry(LineEntry {
             commit_hash: "def456".to_string(),
             author: "Jane Smith".to_string(),
-            timestamp: Utc.timestamp_opt(1234567900, 0).unwrap(),
-            message: "Update line".to_string(),
+            timestamp: Utc.timestamp_opt(1234567900, 0).unwrap().fixed_offset(),
+            subject: "Update line".to_string(),
+            file_path: "test.rs".to_string(),
+            line_number: 42,
             content: "new content".to_string(),
             change_type: ChangeType::Modified,
+            ..Default::default()
         });
 
         let result = formatter.format(&history);
@@ -147,4 +303,262 @@ mod tests {
         assert!(stripped_str.contains("old content"));
         assert!(stripped_str.contains("new content"));
     }
+
+    #[test]
+    fn test_colored_formatter_shows_previous_location() {
+        colored::control::set_override(true);
+        let formatter = ColoredFormatter::new();
+        let mut history = LineHistory::new("src/new.rs".to_string(), 1);
+
+        history.add_entry(LineEntry {
+            commit_hash: "abc123".to_string(),
+            author: "John Doe".to_string(),
+            timestamp: Utc.timestamp_opt(1234567890, 0).unwrap().fixed_offset(),
+            subject: "Initial commit".to_string(),
+            file_path: "old.rs".to_string(),
+            line_number: 3,
+            content: "fn main() {}".to_string(),
+            change_type: ChangeType::Created,
+            ..Default::default()
+        });
+        history.add_entry(LineEntry {
+            commit_hash: "def456".to_string(),
+            author: "John Doe".to_string(),
+            timestamp: Utc.timestamp_opt(1234567900, 0).unwrap().fixed_offset(),
+            subject: "Move file".to_string(),
+            file_path: "src/new.rs".to_string(),
+            line_number: 1,
+            content: "fn main() { run(); }".to_string(),
+            change_type: ChangeType::Modified,
+            ..Default::default()
+        });
+
+        let result = formatter.format(&history);
+        let stripped = strip_ansi_escapes::strip(&result);
+        let stripped_str = String::from_utf8(stripped).unwrap();
+
+        assert!(stripped_str.contains("(Created) old.rs:3\n"));
+        assert!(stripped_str.contains("(Modified)\n"));
+    }
+
+    #[test]
+    fn test_colored_formatter_range() {
+        colored::control::set_override(true);
+        let formatter = ColoredFormatter::new();
+        let mut history = LineHistory::for_range("test.rs".to_string(), 10, 12);
+
+        history.add_entry(LineEntry {
+            commit_hash: "abc123".to_string(),
+            author: "John Doe".to_string(),
+            timestamp: Utc.timestamp_opt(1234567890, 0).unwrap().fixed_offset(),
+            subject: "Initial commit".to_string(),
+            file_path: "test.rs".to_string(),
+            line_number: 10,
+            content: "fn main() {\n    run();".to_string(),
+            change_type: ChangeType::Created,
+            ..Default::default()
+        });
+
+        let result = formatter.format(&history);
+        let stripped = strip_ansi_escapes::strip(&result);
+        let stripped_str = String::from_utf8(stripped).unwrap();
+
+        assert!(stripped_str.starts_with("test.rs:10-12\n"));
+        assert!(stripped_str.contains("\n  fn main() {\n      run();"));
+    }
+
+    #[test]
+    fn test_colored_formatter_file_history() {
+        colored::control::set_override(true);
+        let formatter = ColoredFormatter::new();
+        let mut history = FileHistory::new("test.rs".to_string());
+        let entry = |hash: &str, author: &str, seconds| LineEntry {
+            commit_hash: hash.to_string(),
+            author: author.to_string(),
+            timestamp: Utc.timestamp_opt(seconds, 0).unwrap().fixed_offset(),
+            subject: "Change".to_string(),
+            file_path: "test.rs".to_string(),
+            line_number: 1,
+            content: "fn main() {}".to_string(),
+            change_type: ChangeType::Modified,
+            ..Default::default()
+        };
+
+        history.add_line(FileLine {
+            line_number: 1,
+            content: "fn main() {}".to_string(),
+            entries: vec![
+                entry("abc123456789", "John Doe", 1234567890),
+                entry("def456789012", "Jane", 1234657890),
+            ],
+            latest: Some(1),
+        });
+        history.add_line(FileLine {
+            line_number: 2,
+            content: "".to_string(),
+            entries: vec![entry("abc123456789", "John Doe", 1234567890)],
+            latest: Some(0),
+        });
+
+        let result = formatter.format_file(&history);
+        let stripped = strip_ansi_escapes::strip(&result);
+        let stripped_str = String::from_utf8(stripped).unwrap();
+
+        // The latest change is shown with the number of changes to the line
+        assert_eq!(
+            stripped_str,
+            "test.rs\n\
+             def45678 Jane     2009-02-15 2 1) fn main() {}\n\
+             abc12345 John Doe 2009-02-13 1 2) "
+        );
+    }
+
+    #[test]
+    fn test_colored_formatter_empty_file_history() {
+        colored::control::set_override(true);
+        let formatter = ColoredFormatter::new();
+        let history = FileHistory::new("test.rs".to_string());
+
+        let result = formatter.format_file(&history);
+        let stripped = strip_ansi_escapes::strip(&result);
+        let stripped_str = String::from_utf8(stripped).unwrap();
+
+        assert!(stripped_str.contains("test.rs"));
+        assert!(stripped_str.contains("No lines found"));
+    }
+
+    #[test]
+    fn test_colored_formatter_shows_identities() {
+        colored::control::set_override(true);
+        let formatter = ColoredFormatter::new();
+        let mut history = LineHistory::new("test.rs".to_string(), 1);
+        let entry = LineEntry {
+            commit_hash: "abc123".to_string(),
+            author: "John Doe".to_string(),
+            author_email: "john@example.com".to_string(),
+            timestamp: Utc.timestamp_opt(1234567890, 0).unwrap().fixed_offset(),
+            committer: "John Doe".to_string(),
+            committer_email: "john@example.com".to_string(),
+            committer_timestamp: Utc.timestamp_opt(1234567890, 0).unwrap().fixed_offset(),
+            subject: "Initial commit".to_string(),
+            file_path: "test.rs".to_string(),
+            line_number: 1,
+            content: "fn main() {}".to_string(),
+            change_type: ChangeType::Created,
+            ..Default::default()
+        };
+        history.add_entry(entry.clone());
+        history.add_entry(LineEntry {
+            committer: "Merge Bot".to_string(),
+            committer_email: "bot@example.com".to_string(),
+            committer_timestamp: Utc.timestamp_opt(1234567990, 0).unwrap().fixed_offset(),
+            co_authors: vec![Person {
+                name: "Jane Smith".to_string(),
+                email: "jane@example.com".to_string(),
+            }],
+            subject: "Pair on main".to_string(),
+            ..entry
+        });
+
+        let result = formatter.format(&history);
+        let stripped = strip_ansi_escapes::strip(&result);
+        let stripped_str = String::from_utf8(stripped).unwrap();
+
+        assert!(stripped_str.contains("abc123 John Doe <john@example.com> 2009-02-13 23:31:30"));
+        // The committer is only shown when it is not the author
+        assert_eq!(stripped_str.matches("Committed by").count(), 1);
+        assert!(
+            stripped_str.contains("Committed by Merge Bot <bot@example.com> 2009-02-13 23:33:10")
+        );
+        assert!(stripped_str.contains("Co-authored by Jane Smith <jane@example.com>"));
+    }
+
+    #[test]
+    fn test_colored_formatter_full_message() {
+        colored::control::set_override(true);
+        let mut history = LineHistory::new("test.rs".to_string(), 1);
+        history.add_entry(LineEntry {
+            commit_hash: "abc123".to_string(),
+            author: "John Doe".to_string(),
+            timestamp: Utc.timestamp_opt(1234567890, 0).unwrap().fixed_offset(),
+            committer: "John Doe".to_string(),
+            committer_timestamp: Utc.timestamp_opt(1234567890, 0).unwrap().fixed_offset(),
+            subject: "Fix parser".to_string(),
+            body: "It broke on long lines.".to_string(),
+            trailers: vec![Trailer {
+                key: "Signed-off-by".to_string(),
+                value: "John Doe <john@example.com>".to_string(),
+            }],
+            file_path: "test.rs".to_string(),
+            line_number: 1,
+            content: "fn main() {}".to_string(),
+            change_type: ChangeType::Modified,
+            ..Default::default()
+        });
+
+        let strip = |output: String| String::from_utf8(strip_ansi_escapes::strip(output)).unwrap();
+
+        // Only the subject by default
+        let output = strip(ColoredFormatter::new().format(&history));
+        assert!(output.contains("\nFix parser\n  fn main() {}"));
+        assert!(!output.contains("It broke"));
+
+        let output = strip(
+            ColoredFormatter::new()
+                .with_full_message(true)
+                .format(&history),
+        );
+        assert!(output.contains(
+            "\nFix parser\n\nIt broke on long lines.\n\nSigned-off-by: John Doe <john@example.com>\n  fn main() {}"
+        ));
+    }
+
+    #[test]
+    fn test_colored_formatter_shows_hunks() {
+        colored::control::set_override(true);
+        let mut history = LineHistory::new("test.rs".to_string(), 2);
+        history.add_entry(LineEntry {
+            commit_hash: "abc123".to_string(),
+            author: "John Doe".to_string(),
+            timestamp: Utc.timestamp_opt(1234567890, 0).unwrap().fixed_offset(),
+            committer: "John Doe".to_string(),
+            committer_timestamp: Utc.timestamp_opt(1234567890, 0).unwrap().fixed_offset(),
+            subject: "Greet".to_string(),
+            file_path: "test.rs".to_string(),
+            line_number: 2,
+            content: "    hello();".to_string(),
+            change_type: ChangeType::Modified,
+            hunks: vec![DiffHunk {
+                old_start: 1,
+                old_lines: 2,
+                new_start: 1,
+                new_lines: 2,
+                lines: vec![
+                    DiffLine {
+                        kind: DiffLineKind::Context,
+                        content: "fn main() {".to_string(),
+                    },
+                    DiffLine {
+                        kind: DiffLineKind::Removed,
+                        content: "    hi();".to_string(),
+                    },
+                    DiffLine {
+                        kind: DiffLineKind::Added,
+                        content: "    hello();".to_string(),
+                    },
+                ],
+            }],
+            ..Default::default()
+        });
+
+        let result = ColoredFormatter::new().format(&history);
+        let stripped = String::from_utf8(strip_ansi_escapes::strip(&result)).unwrap();
+
+        assert!(
+            stripped
+                .ends_with("  hello();\n@@ -1,2 +1,2 @@\n fn main() {\n-    hi();\n+    hello();")
+        );
+        assert!(result.contains(&"-    hi();".red().to_string()));
+        assert!(result.contains(&"+    hello();".green().to_string()));
+    }
 }
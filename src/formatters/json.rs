@@ -1,5 +1,5 @@
 use crate::core::formatting::OutputFormatter;
-use crate::core::line_history::LineHistory;
+use crate::core::line_history::{FileHistory, LineHistory};
 
 pub struct JsonFormatter;
 
@@ -9,16 +9,26 @@ impl JsonFormatter {
     }
 }
 
+impl Default for JsonFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl OutputFormatter for JsonFormatter {
     fn format(&self, history: &LineHistory) -> String {
         serde_json::to_string_pretty(history).unwrap_or_else(|_| "{}".to_string())
     }
+
+    fn format_file(&self, history: &FileHistory) -> String {
+        serde_json::to_string_pretty(history).unwrap_or_else(|_| "{}".to_string())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::{ChangeType, LineEntry};
+    use crate::domain::{ChangeType, FileLine, LineEntry};
     use chrono::{TimeZone, Utc};
 
     #[test]
@@ -41,17 +51,25 @@ mod tests {
         history.add_entry(LineEntry {
             commit_hash: "abc123".to_string(),
             author: "John Doe".to_string(),
-            timestamp: Utc.timestamp_opt(1234567890, 0).unwrap(),
-            message: "Initial commit".to_string(),
+            timestamp: Utc.timestamp_opt(1234567890, 0).unwrap().fixed_offset(),
+            subject: "Initial commit".to_string(),
+            file_path: "test.rs".to_string(),
+            line_number: 42,
             content: "println!(\"Hello, world!\");".to_string(),
             change_type: ChangeType::Created,
+            ..Default::default()
         });
 
         let result = formatter.format(&history);
 
         assert!(result.contains("\"commit_hash\": \"abc123\""));
         assert!(result.contains("\"author\": \"John Doe\""));
-        assert!(result.contains("\"message\": \"Initial commit\""));
+        assert!(result.contains("\"author_email\": \"\""));
+        assert!(result.contains("\"committer\": \"\""));
+        assert!(result.contains("\"committer_timestamp\""));
+        // Co-authors are left out when there are none
+        assert!(!result.contains("co_authors"));
+        assert!(result.contains("\"subject\": \"Initial commit\""));
         assert!(result.contains("\"Created\""));
     }
 
@@ -67,4 +85,48 @@ mod tests {
         assert_eq!(parsed["file_path"], "test.rs");
         assert_eq!(parsed["line_number"], 42);
     }
+
+    #[test]
+    fn test_json_formatter_range() {
+        let formatter = JsonFormatter::new();
+        let history = LineHistory::for_range("test.rs".to_string(), 10, 40);
+
+        let parsed: serde_json::Value = serde_json::from_str(&formatter.format(&history)).unwrap();
+        assert_eq!(parsed["line_number"], 10);
+        assert_eq!(parsed["end_line_number"], 40);
+
+        // Single lines do not have an end
+        let history = LineHistory::new("test.rs".to_string(), 42);
+        assert!(!formatter.format(&history).contains("end_line_number"));
+    }
+
+    #[test]
+    fn test_json_formatter_file_history() {
+        let formatter = JsonFormatter::new();
+        let mut history = FileHistory::new("test.rs".to_string());
+        history.add_line(FileLine {
+            line_number: 1,
+            content: "fn main() {}".to_string(),
+            entries: vec![LineEntry {
+                commit_hash: "abc123".to_string(),
+                author: "John Doe".to_string(),
+                timestamp: Utc.timestamp_opt(1234567890, 0).unwrap().fixed_offset(),
+                subject: "Initial commit".to_string(),
+                file_path: "test.rs".to_string(),
+                line_number: 1,
+                content: "fn main() {}".to_string(),
+                change_type: ChangeType::Created,
+                ..Default::default()
+            }],
+            latest: Some(0),
+        });
+
+        let parsed: serde_json::Value =
+            serde_json::from_str(&formatter.format_file(&history)).unwrap();
+        assert_eq!(parsed["file_path"], "test.rs");
+        assert_eq!(parsed["lines"][0]["line_number"], 1);
+        assert_eq!(parsed["lines"][0]["content"], "fn main() {}");
+        assert_eq!(parsed["lines"][0]["entries"][0]["commit_hash"], "abc123");
+        assert!(parsed["lines"][0].get("latest").is_none());
+    }
 }
@@ -1,34 +1,97 @@
-use crate::core::formatting::OutputFormatter;
-use crate::core::line_history::LineHistory;
+use crate::core::formatting::{DateFormat, OutputFormatter, person};
+use crate::core::line_history::{FileHistory, LineHistory};
+use chrono::{DateTime, FixedOffset};
 use tabled::{Table, Tabled};
 
-pub struct TableFormatter;
+pub struct TableFormatter {
+    full_message: bool,
+    date_format: Option<DateFormat>,
+}
 
 #[derive(Tabled)]
 struct TableEntry {
     #[tabled(rename = "Commit")]
     commit_hash: String,
+    /// Where the line was in the commit, which differs after a rename or move
+    #[tabled(rename = "Location")]
+    location: String,
     #[tabled(rename = "Author")]
     author: String,
     #[tabled(rename = "Timestamp")]
     timestamp: String,
+    #[tabled(rename = "Co-authors")]
+    co_authors: String,
+    #[tabled(rename = "Committer")]
+    committer: String,
+    #[tabled(rename = "Committed")]
+    committer_timestamp: String,
     #[tabled(rename = "Message")]
     message: String,
     #[tabled(rename = "Change Type")]
     change_type: String,
 }
 
+#[derive(Tabled)]
+struct FileTableEntry {
+    #[tabled(rename = "Line")]
+    line_number: u32,
+    #[tabled(rename = "Changes")]
+    changes: usize,
+    #[tabled(rename = "Commit")]
+    commit_hash: String,
+    #[tabled(rename = "Author")]
+    author: String,
+    #[tabled(rename = "Timestamp")]
+    timestamp: String,
+    #[tabled(rename = "Content")]
+    content: String,
+}
+
 impl TableFormatter {
     pub fn new() -> Self {
-        Self
+        Self {
+            full_message: false,
+            date_format: None,
+        }
+    }
+
+    /// Shows the whole commit message of each entry instead of its subject.
+    pub fn with_full_message(mut self, enabled: bool) -> Self {
+        self.full_message = enabled;
+        self
+    }
+
+    /// Shows dates in the given format instead of ISO dates.
+    pub fn with_date_format(mut self, date_format: Option<DateFormat>) -> Self {
+        self.date_format = date_format;
+        self
+    }
+
+    fn date(&self, date: &DateTime<FixedOffset>) -> String {
+        self.date_format
+            .as_ref()
+            .unwrap_or(&DateFormat::Iso)
+            .format(date)
+    }
+}
+
+impl Default for TableFormatter {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl OutputFormatter for TableFormatter {
     fn format(&self, history: &LineHistory) -> String {
+        let label = match history.end_line_number {
+            Some(_) => "Lines",
+            None => "Line",
+        };
         let header = format!(
-            "File: {}\nLine: {}\n\n",
-            history.file_path, history.line_number
+            "File: {}\n{}: {}\n\n",
+            history.file_path,
+            label,
+            history.span()
         );
 
         if history.entries.is_empty() {
@@ -41,9 +104,22 @@ impl OutputFormatter for TableFormatter {
             .map(|entry| {
                 TableEntry {
                     commit_hash: entry.commit_hash.chars().take(8).collect(), // Truncate commit hash
-                    author: entry.author.clone(),
-                    timestamp: entry.timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
-                    message: entry.message.clone(),
+                    location: format!("{}:{}", entry.file_path, entry.line_number),
+                    author: person(&entry.author, &entry.author_email),
+                    timestamp: self.date(&entry.timestamp),
+                    co_authors: entry
+                        .co_authors
+                        .iter()
+                        .map(|co_author| person(&co_author.name, &co_author.email))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    committer: person(&entry.committer, &entry.committer_email),
+                    committer_timestamp: self.date(&entry.committer_timestamp),
+                    message: if self.full_message {
+                        entry.full_message()
+                    } else {
+                        entry.subject.clone()
+                    },
                     change_type: entry.change_type.to_string(),
                 }
             })
@@ -52,13 +128,45 @@ impl OutputFormatter for TableFormatter {
         let table = Table::new(table_entries).to_string();
         format!("{}{}", header, table)
     }
+
+    fn format_file(&self, history: &FileHistory) -> String {
+        let header = format!("File: {}\n\n", history.file_path);
+
+        if history.lines.is_empty() {
+            return format!("{}No lines", header);
+        }
+
+        // Each line shows its latest change
+        let table_entries: Vec<FileTableEntry> = history
+            .lines
+            .iter()
+            .map(|line| {
+                let latest = line.latest_entry();
+                FileTableEntry {
+                    line_number: line.line_number,
+                    changes: line.entries.len(),
+                    commit_hash: latest
+                        .map(|entry| entry.commit_hash.chars().take(8).collect())
+                        .unwrap_or_default(),
+                    author: latest.map(|entry| entry.author.clone()).unwrap_or_default(),
+                    timestamp: latest
+                        .map(|entry| self.date(&entry.timestamp))
+                        .unwrap_or_default(),
+                    content: line.content.clone(),
+                }
+            })
+            .collect();
+
+        let table = Table::new(table_entries).to_string();
+        format!("{}{}", header, table)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::{ChangeType, LineEntry};
-    use chrono::{DateTime, Utc};
+    use crate::domain::{ChangeType, FileLine, LineEntry, Person};
+    use chrono::DateTime;
 
     #[test]
     fn test_table_formatter_empty_history() {
@@ -81,12 +189,21 @@ mod tests {
         let entry = LineEntry {
             commit_hash: "abc123".to_string(),
             author: "Test Author".to_string(),
-            timestamp: DateTime::parse_from_rfc3339("2023-01-01T00:00:00Z")
-                .unwrap()
-                .with_timezone(&Utc),
-            message: "Test commit".to_string(),
+            author_email: "author@example.com".to_string(),
+            timestamp: DateTime::parse_from_rfc3339("2023-01-01T00:00:00Z").unwrap(),
+            committer: "Merge Bot".to_string(),
+            committer_email: "bot@example.com".to_string(),
+            committer_timestamp: DateTime::parse_from_rfc3339("2023-01-02T00:00:00Z").unwrap(),
+            co_authors: vec![Person {
+                name: "Pair Partner".to_string(),
+                email: "pair@example.com".to_string(),
+            }],
+            subject: "Test commit".to_string(),
+            file_path: "test.rs".to_string(),
+            line_number: 42,
             content: "test content".to_string(),
             change_type: ChangeType::Created,
+            ..Default::default()
         };
 
         history.add_entry(entry);
@@ -101,7 +218,12 @@ mod tests {
         assert!(output.contains("Message"));
         assert!(output.contains("Change Type"));
         assert!(output.contains("abc123"));
-        assert!(output.contains("Test Author"));
+        assert!(output.contains("Test Author <author@example.com>"));
+        assert!(output.contains("Co-authors"));
+        assert!(output.contains("Pair Partner <pair@example.com>"));
+        assert!(output.contains("Committer"));
+        assert!(output.contains("Merge Bot <bot@example.com>"));
+        assert!(output.contains("2023-01-02 00:00:00 +0000"));
         assert!(output.contains("Test commit"));
         assert!(output.contains("Created"));
     }
@@ -114,23 +236,25 @@ mod tests {
         let entry1 = LineEntry {
             commit_hash: "abc123".to_string(),
             author: "Test Author 1".to_string(),
-            timestamp: DateTime::parse_from_rfc3339("2023-01-01T00:00:00Z")
-                .unwrap()
-                .with_timezone(&Utc),
-            message: "First commit".to_string(),
+            timestamp: DateTime::parse_from_rfc3339("2023-01-01T00:00:00Z").unwrap(),
+            subject: "First commit".to_string(),
+            file_path: "test.rs".to_string(),
+            line_number: 42,
             content: "test content 1".to_string(),
             change_type: ChangeType::Created,
+            ..Default::default()
         };
 
         let entry2 = LineEntry {
             commit_hash: "def456".to_string(),
             author: "Test Author 2".to_string(),
-            timestamp: DateTime::parse_from_rfc3339("2023-01-02T00:00:00Z")
-                .unwrap()
-                .with_timezone(&Utc),
-            message: "Second commit".to_string(),
+            timestamp: DateTime::parse_from_rfc3339("2023-01-02T00:00:00Z").unwrap(),
+            subject: "Second commit".to_string(),
+            file_path: "test.rs".to_string(),
+            line_number: 42,
             content: "test content 2".to_string(),
             change_type: ChangeType::Modified,
+            ..Default::default()
         };
 
         history.add_entry(entry1);
@@ -148,4 +272,75 @@ mod tests {
         assert!(output.contains("Created"));
         assert!(output.contains("Modified"));
     }
+
+    #[test]
+    fn test_table_formatter_location() {
+        let formatter = TableFormatter::new();
+        let mut history = LineHistory::new("src/app.rs".to_string(), 2);
+
+        // The line was in main.rs until it was moved
+        history.add_entry(LineEntry {
+            commit_hash: "abc123".to_string(),
+            author: "Test Author".to_string(),
+            timestamp: DateTime::parse_from_rfc3339("2023-01-01T00:00:00Z").unwrap(),
+            subject: "Create main".to_string(),
+            file_path: "main.rs".to_string(),
+            line_number: 5,
+            change_type: ChangeType::Created,
+            ..Default::default()
+        });
+        history.add_entry(LineEntry {
+            commit_hash: "def456".to_string(),
+            author: "Test Author".to_string(),
+            timestamp: DateTime::parse_from_rfc3339("2023-01-02T00:00:00Z").unwrap(),
+            subject: "Move to src/app.rs".to_string(),
+            file_path: "src/app.rs".to_string(),
+            line_number: 2,
+            change_type: ChangeType::Modified,
+            ..Default::default()
+        });
+
+        let output = formatter.format(&history);
+
+        assert!(output.contains("Location"));
+        assert!(output.contains("main.rs:5"));
+        assert!(output.contains("src/app.rs:2"));
+    }
+
+    #[test]
+    fn test_table_formatter_file_history() {
+        let formatter = TableFormatter::new();
+        let mut history = FileHistory::new("test.rs".to_string());
+
+        history.add_line(FileLine {
+            line_number: 1,
+            content: "test content".to_string(),
+            entries: vec![LineEntry {
+                commit_hash: "abc123".to_string(),
+                author: "Test Author".to_string(),
+                timestamp: DateTime::parse_from_rfc3339("2023-01-01T00:00:00Z").unwrap(),
+                subject: "Test commit".to_string(),
+                file_path: "test.rs".to_string(),
+                line_number: 1,
+                content: "test content".to_string(),
+                change_type: ChangeType::Created,
+                ..Default::default()
+            }],
+            latest: Some(0),
+        });
+
+        let output = formatter.format_file(&history);
+
+        assert!(output.contains("File: test.rs"));
+        assert!(output.contains("Line"));
+        assert!(output.contains("Changes"));
+        assert!(output.contains("Content"));
+        assert!(output.contains("abc123"));
+        assert!(output.contains("Test Author"));
+        assert!(output.contains("2023-01-01 00:00:00 +0000"));
+        assert!(output.contains("test content"));
+
+        let empty = FileHistory::new("test.rs".to_string());
+        assert!(formatter.format_file(&empty).contains("No lines"));
+    }
 }
@@ -1,5 +1,5 @@
 use crate::core::formatting::OutputFormatter;
-use crate::core::line_history::LineHistory;
+use crate::core::line_history::{FileHistory, LineHistory};
 
 pub struct YamlFormatter;
 
@@ -9,17 +9,27 @@ impl YamlFormatter {
     }
 }
 
+impl Default for YamlFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl OutputFormatter for YamlFormatter {
     fn format(&self, history: &LineHistory) -> String {
         serde_yaml::to_string(history).unwrap_or_else(|_| "Error formatting YAML".to_string())
     }
+
+    fn format_file(&self, history: &FileHistory) -> String {
+        serde_yaml::to_string(history).unwrap_or_else(|_| "Error formatting YAML".to_string())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::{ChangeType, LineEntry};
-    use chrono::{DateTime, Utc};
+    use crate::domain::{ChangeType, FileLine, LineEntry};
+    use chrono::DateTime;
 
     #[test]
     fn test_yaml_formatter_empty_history() {
@@ -42,12 +52,13 @@ mod tests {
         let entry = LineEntry {
             commit_hash: "abc123".to_string(),
             author: "Test Author".to_string(),
-            timestamp: DateTime::parse_from_rfc3339("2023-01-01T00:00:00Z")
-                .unwrap()
-                .with_timezone(&Utc),
-            message: "Test commit".to_string(),
+            timestamp: DateTime::parse_from_rfc3339("2023-01-01T00:00:00Z").unwrap(),
+            subject: "Test commit".to_string(),
+            file_path: "test.rs".to_string(),
+            line_number: 42,
             content: "test content".to_string(),
             change_type: ChangeType::Created,
+            ..Default::default()
         };
 
         history.add_entry(entry);
@@ -59,7 +70,7 @@ mod tests {
         assert!(output.contains("line_number: 42"));
         assert!(output.contains("commit_hash: abc123"));
         assert!(output.contains("author: Test Author"));
-        assert!(output.contains("message: Test commit"));
+        assert!(output.contains("subject: Test commit"));
         assert!(output.contains("change_type: Created"));
     }
 
@@ -71,12 +82,13 @@ mod tests {
         let entry = LineEntry {
             commit_hash: "abc123".to_string(),
             author: "Test Author".to_string(),
-            timestamp: DateTime::parse_from_rfc3339("2023-01-01T00:00:00Z")
-                .unwrap()
-                .with_timezone(&Utc),
-            message: "Test commit".to_string(),
+            timestamp: DateTime::parse_from_rfc3339("2023-01-01T00:00:00Z").unwrap(),
+            subject: "Test commit".to_string(),
+            file_path: "test.rs".to_string(),
+            line_number: 42,
             content: "test content".to_string(),
             change_type: ChangeType::Created,
+            ..Default::default()
         };
 
         history.add_entry(entry);
@@ -89,4 +101,33 @@ mod tests {
         assert!(parsed.get("line_number").is_some());
         assert!(parsed.get("entries").is_some());
     }
+
+    #[test]
+    fn test_yaml_formatter_file_history() {
+        let formatter = YamlFormatter::new();
+        let mut history = FileHistory::new("test.rs".to_string());
+        history.add_line(FileLine {
+            line_number: 1,
+            content: "test content".to_string(),
+            entries: vec![LineEntry {
+                commit_hash: "abc123".to_string(),
+                author: "Test Author".to_string(),
+                timestamp: DateTime::parse_from_rfc3339("2023-01-01T00:00:00Z").unwrap(),
+                subject: "Test commit".to_string(),
+                file_path: "test.rs".to_string(),
+                line_number: 1,
+                content: "test content".to_string(),
+                change_type: ChangeType::Created,
+                ..Default::default()
+            }],
+            latest: Some(0),
+        });
+
+        let output = formatter.format_file(&history);
+
+        let parsed: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(parsed["file_path"], "test.rs");
+        assert_eq!(parsed["lines"][0]["line_number"], 1);
+        assert_eq!(parsed["lines"][0]["entries"][0]["commit_hash"], "abc123");
+    }
 }
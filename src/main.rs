@@ -1,8 +1,8 @@
 use anyhow::Result;
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use git_ombl::{
-    ColoredFormatter, GitAdapter, JsonFormatter, LineHistoryUseCase, OutputFormatter, SortOrder,
-    TableFormatter, YamlFormatter,
+    Cache, ColoredFormatter, CommitOrder, DateFormat, GitAdapter, HistoryOptions, JsonFormatter,
+    LineHistoryUseCase, LineSelector, OutputFormatter, SortOrder, TableFormatter, YamlFormatter,
 };
 use std::env;
 
@@ -10,18 +10,29 @@ use std::env;
 #[command(name = "git-ombl")]
 #[command(about = "Git blame wrapper to display the full commit history for a line")]
 #[command(version)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// File path to analyze
-    file: String,
+    #[arg(required = true)]
+    file: Option<String>,
+
+    /// Line number to analyze; without a line or range, every line of the file is analyzed
+    line: Option<u32>,
 
-    /// Line number to analyze
-    line: u32,
+    /// Range of lines to analyze instead of a single line: "start,end", "start,+count",
+    /// "/regex/[,end]" or ":funcname"
+    #[arg(short = 'L', long, value_name = "RANGE", conflicts_with = "line")]
+    range: Option<LineSelector>,
 
     /// Output format
     #[arg(short, long, default_value = "colored")]
     format: Format,
 
-    /// Maximum number of commits to traverse
+    /// Maximum number of history entries to find; no more commits are diffed once they are found,
+    /// unless ordering by date, which looks at the whole history to keep the newest ones
     #[arg(short, long)]
     limit: Option<usize>,
 
@@ -29,17 +40,110 @@ struct Cli {
     #[arg(short, long, default_value = "asc")]
     sort: SortOrder,
 
-    /// Ignore changes made by the specified revision(s)
+    /// Order commits by their place in the ancestry, or by author or committer date
+    #[arg(long, default_value = "topo")]
+    order: CommitOrder,
+
+    /// Ignore changes made by the specified revision(s), attributing lines to the change before them
     #[arg(long = "ignore-rev")]
     ignore_revs: Vec<String>,
 
-    /// Show commits more recent than a specific date (e.g., "2023-01-01", "2023-01-01T12:00:00Z")
+    /// Ignore the revisions listed in the specified file(s), one per line
+    #[arg(long = "ignore-revs-file")]
+    ignore_revs_files: Vec<String>,
+
+    /// Do not use the ignore-revs file configured with blame.ignoreRevsFile
+    #[arg(long)]
+    no_ignore_revs_file: bool,
+
+    /// Show commits more recent than a specific date (e.g., "2023-01-01", "2023-06",
+    /// "2023-01-01T12:00:00Z", "2 weeks ago", "yesterday", "last monday")
     #[arg(long)]
     since: Option<String>,
 
-    /// Show commits older than a specific date (e.g., "2023-12-31", "2023-12-31T23:59:59Z")
+    /// Show commits older than a specific date (e.g., "2023-12-31", "2023-12-31T23:59:59Z",
+    /// "3.months.ago")
     #[arg(long)]
     until: Option<String>,
+
+    /// Timezone of bare dates given to --since and --until, as an offset like "+0200" or "UTC";
+    /// local time by default
+    #[arg(long, value_name = "OFFSET")]
+    timezone: Option<String>,
+
+    /// Revision or range to analyze instead of HEAD (e.g., "v1.0", "HEAD~20", "v1.0..main")
+    #[arg(long)]
+    rev: Option<String>,
+
+    /// Follow only the first parent of merge commits, attributing changes merged in to the merge
+    #[arg(long)]
+    first_parent: bool,
+
+    /// Leave out merge commits, while still following the line through them
+    #[arg(long)]
+    no_merges: bool,
+
+    /// Keep following a line past the commit that deleted it before it was re-created
+    #[arg(long)]
+    follow_deleted: bool,
+
+    /// Detect lines moved or copied from other files changed in the same commit
+    #[arg(short = 'C', long)]
+    find_copies: bool,
+
+    /// Ignore whitespace when comparing lines, skipping commits that only reindent the line
+    #[arg(short = 'w', long)]
+    ignore_whitespace: bool,
+
+    /// Ignore changes in the amount of whitespace when comparing lines
+    #[arg(long)]
+    ignore_space_change: bool,
+
+    /// Ignore blank lines added or removed around the line
+    #[arg(long)]
+    ignore_blank_lines: bool,
+
+    /// Show the hunk of the diff that changed the line in each entry
+    #[arg(short = 'p', long)]
+    patch: bool,
+
+    /// Number of context lines around the hunks; implies --patch
+    #[arg(short = 'U', long, value_name = "N")]
+    unified: Option<u32>,
+
+    /// Date format, like `git log --date`: relative, local, iso, iso-strict, short, unix or
+    /// format:<strftime>
+    #[arg(long, value_name = "FORMAT")]
+    date: Option<DateFormat>,
+
+    /// Show the whole commit message of each entry instead of its subject
+    #[arg(long)]
+    full_message: bool,
+
+    /// Show names and emails as recorded instead of mapping them through .mailmap
+    #[arg(long)]
+    no_mailmap: bool,
+
+    /// Do not read or write the cache of diff results in .git/ombl
+    #[arg(long)]
+    no_cache: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage the cache of diff results in .git/ombl
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+}
+
+#[derive(Subcommand, Clone, Debug, PartialEq)]
+enum CacheAction {
+    /// Show where the cache is and how much it holds
+    Stats,
+    /// Remove everything from the cache
+    Clear,
 }
 
 #[derive(Clone, Debug, PartialEq, ValueEnum)]
@@ -53,40 +157,92 @@ enum Format {
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
-    // Get current directory as repository root
+    // Discover the repository from the current directory (honoring GIT_DIR and linked worktrees)
     let current_dir = env::current_dir()?;
 
+    if let Some(Command::Cache { action }) = cli.command {
+        let git_adapter = GitAdapter::new(&current_dir)?;
+        return run_cache_action(git_adapter.cache(), action);
+    }
+    let file = cli.file.expect("clap requires a file without a subcommand");
+
     // Create git adapter
-    let git_adapter = GitAdapter::new(&current_dir)?;
+    let git_adapter = GitAdapter::new(&current_dir)?
+        .with_follow_deleted(cli.follow_deleted)
+        .with_find_copies(cli.find_copies)
+        .with_ignore_whitespace(cli.ignore_whitespace)
+        .with_ignore_space_change(cli.ignore_space_change)
+        .with_ignore_blank_lines(cli.ignore_blank_lines)
+        .with_first_parent(cli.first_parent)
+        .with_mailmap(!cli.no_mailmap)
+        .with_patch((cli.patch || cli.unified.is_some()).then(|| cli.unified.unwrap_or(3)))
+        .with_cache(!cli.no_cache);
 
     // Create use case
     let use_case = LineHistoryUseCase::new(git_adapter);
 
-    // Get line history
-    let history = use_case.get_line_history(
-        &cli.file,
-        cli.line,
-        cli.sort,
-        &cli.ignore_revs,
-        cli.since.as_deref(),
-        cli.until.as_deref(),
-    )?;
+    let options = HistoryOptions {
+        sort_order: cli.sort,
+        order: cli.order,
+        ignore_revs: cli.ignore_revs,
+        ignore_revs_files: cli.ignore_revs_files,
+        no_ignore_revs_file: cli.no_ignore_revs_file,
+        since: cli.since,
+        until: cli.until,
+        timezone: cli.timezone,
+        no_merges: cli.no_merges,
+        limit: cli.limit,
+        rev: cli.rev,
+    };
 
     // Create formatter based on format choice
     let formatter: Box<dyn OutputFormatter> = match cli.format {
-        Format::Colored => Box::new(ColoredFormatter::new()),
+        Format::Colored => Box::new(
+            ColoredFormatter::new()
+                .with_full_message(cli.full_message)
+                .with_date_format(cli.date.clone()),
+        ),
         Format::Json => Box::new(JsonFormatter::new()),
-        Format::Table => Box::new(TableFormatter::new()),
+        Format::Table => Box::new(
+            TableFormatter::new()
+                .with_full_message(cli.full_message)
+                .with_date_format(cli.date.clone()),
+        ),
         Format::Yaml => Box::new(YamlFormatter::new()),
     };
 
-    // Format and output
-    let output = formatter.format(&history);
+    // Get the history of the line, range or whole file, and format it
+    let output = match (&cli.range, cli.line) {
+        (Some(selector), _) => {
+            formatter.format(&use_case.get_line_range_history(&file, selector, &options)?)
+        }
+        (None, Some(line)) => formatter.format(&use_case.get_line_history(&file, line, &options)?),
+        (None, None) => formatter.format_file(&use_case.get_file_history(&file, &options)?),
+    };
     println!("{}", output);
 
     Ok(())
 }
 
+fn run_cache_action(cache: &Cache, action: CacheAction) -> Result<()> {
+    match action {
+        CacheAction::Stats => {
+            let stats = cache.stats()?;
+            println!("Location: {}", stats.location.display());
+            println!("Diffs:    {}", stats.diffs);
+            println!("Renames:  {}", stats.renames);
+            println!("Lines:    {}", stats.lines);
+            println!("Size:     {} bytes", stats.size);
+        }
+        CacheAction::Clear => {
+            cache.clear()?;
+            println!("Cleared {}", cache.stats()?.location.display());
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -112,53 +268,53 @@ mod tests {
 
     #[test]
     fn test_cli_parsing() {
-        let cli = Cli::parse_from(&["git-ombl", "test.rs", "42", "--format", "json"]);
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--format", "json"]);
 
-        assert_eq!(cli.file, "test.rs");
-        assert_eq!(cli.line, 42);
+        assert_eq!(cli.file.as_deref(), Some("test.rs"));
+        assert_eq!(cli.line, Some(42));
         assert!(matches!(cli.format, Format::Json));
     }
 
     #[test]
     fn test_cli_parsing_with_sort_desc() {
-        let cli = Cli::parse_from(&["git-ombl", "test.rs", "42", "--sort", "desc"]);
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--sort", "desc"]);
 
-        assert_eq!(cli.file, "test.rs");
-        assert_eq!(cli.line, 42);
+        assert_eq!(cli.file.as_deref(), Some("test.rs"));
+        assert_eq!(cli.line, Some(42));
         assert!(matches!(cli.sort, SortOrder::Desc));
     }
 
     #[test]
     fn test_cli_parsing_with_sort_asc() {
-        let cli = Cli::parse_from(&["git-ombl", "test.rs", "42", "--sort", "asc"]);
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--sort", "asc"]);
 
-        assert_eq!(cli.file, "test.rs");
-        assert_eq!(cli.line, 42);
+        assert_eq!(cli.file.as_deref(), Some("test.rs"));
+        assert_eq!(cli.line, Some(42));
         assert!(matches!(cli.sort, SortOrder::Asc));
     }
 
     #[test]
     fn test_cli_parsing_default_sort() {
-        let cli = Cli::parse_from(&["git-ombl", "test.rs", "42"]);
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42"]);
 
-        assert_eq!(cli.file, "test.rs");
-        assert_eq!(cli.line, 42);
+        assert_eq!(cli.file.as_deref(), Some("test.rs"));
+        assert_eq!(cli.line, Some(42));
         assert!(matches!(cli.sort, SortOrder::Asc));
     }
 
     #[test]
     fn test_cli_parsing_with_single_ignore_rev() {
-        let cli = Cli::parse_from(&["git-ombl", "test.rs", "42", "--ignore-rev", "abc123def"]);
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--ignore-rev", "abc123def"]);
 
-        assert_eq!(cli.file, "test.rs");
-        assert_eq!(cli.line, 42);
+        assert_eq!(cli.file.as_deref(), Some("test.rs"));
+        assert_eq!(cli.line, Some(42));
         assert_eq!(cli.ignore_revs.len(), 1);
         assert_eq!(cli.ignore_revs[0], "abc123def");
     }
 
     #[test]
     fn test_cli_parsing_with_multiple_ignore_revs() {
-        let cli = Cli::parse_from(&[
+        let cli = Cli::parse_from([
             "git-ombl",
             "test.rs",
             "42",
@@ -168,8 +324,8 @@ mod tests {
             "def456ghi",
         ]);
 
-        assert_eq!(cli.file, "test.rs");
-        assert_eq!(cli.line, 42);
+        assert_eq!(cli.file.as_deref(), Some("test.rs"));
+        assert_eq!(cli.line, Some(42));
         assert_eq!(cli.ignore_revs.len(), 2);
         assert_eq!(cli.ignore_revs[0], "abc123def");
         assert_eq!(cli.ignore_revs[1], "def456ghi");
@@ -177,10 +333,10 @@ mod tests {
 
     #[test]
     fn test_cli_parsing_with_no_ignore_revs() {
-        let cli = Cli::parse_from(&["git-ombl", "test.rs", "42"]);
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42"]);
 
-        assert_eq!(cli.file, "test.rs");
-        assert_eq!(cli.line, 42);
+        assert_eq!(cli.file.as_deref(), Some("test.rs"));
+        assert_eq!(cli.line, Some(42));
         assert!(cli.ignore_revs.is_empty());
     }
 
@@ -211,27 +367,27 @@ mod tests {
 
     #[test]
     fn test_cli_parsing_with_since_option() {
-        let cli = Cli::parse_from(&["git-ombl", "test.rs", "42", "--since", "2023-01-01"]);
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--since", "2023-01-01"]);
 
-        assert_eq!(cli.file, "test.rs");
-        assert_eq!(cli.line, 42);
+        assert_eq!(cli.file.as_deref(), Some("test.rs"));
+        assert_eq!(cli.line, Some(42));
         assert_eq!(cli.since, Some("2023-01-01".to_string()));
         assert_eq!(cli.until, None);
     }
 
     #[test]
     fn test_cli_parsing_with_until_option() {
-        let cli = Cli::parse_from(&["git-ombl", "test.rs", "42", "--until", "2023-12-31"]);
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--until", "2023-12-31"]);
 
-        assert_eq!(cli.file, "test.rs");
-        assert_eq!(cli.line, 42);
+        assert_eq!(cli.file.as_deref(), Some("test.rs"));
+        assert_eq!(cli.line, Some(42));
         assert_eq!(cli.since, None);
         assert_eq!(cli.until, Some("2023-12-31".to_string()));
     }
 
     #[test]
     fn test_cli_parsing_with_both_since_and_until() {
-        let cli = Cli::parse_from(&[
+        let cli = Cli::parse_from([
             "git-ombl",
             "test.rs",
             "42",
@@ -241,15 +397,15 @@ mod tests {
             "2023-12-31T23:59:59Z",
         ]);
 
-        assert_eq!(cli.file, "test.rs");
-        assert_eq!(cli.line, 42);
+        assert_eq!(cli.file.as_deref(), Some("test.rs"));
+        assert_eq!(cli.line, Some(42));
         assert_eq!(cli.since, Some("2023-01-01T00:00:00Z".to_string()));
         assert_eq!(cli.until, Some("2023-12-31T23:59:59Z".to_string()));
     }
 
     #[test]
     fn test_cli_parsing_with_since_rfc2822_format() {
-        let cli = Cli::parse_from(&[
+        let cli = Cli::parse_from([
             "git-ombl",
             "test.rs",
             "42",
@@ -257,14 +413,14 @@ mod tests {
             "Mon, 01 Jan 2023 00:00:00 GMT",
         ]);
 
-        assert_eq!(cli.file, "test.rs");
-        assert_eq!(cli.line, 42);
+        assert_eq!(cli.file.as_deref(), Some("test.rs"));
+        assert_eq!(cli.line, Some(42));
         assert_eq!(cli.since, Some("Mon, 01 Jan 2023 00:00:00 GMT".to_string()));
     }
 
     #[test]
     fn test_cli_parsing_with_since_and_ignore_rev_combined() {
-        let cli = Cli::parse_from(&[
+        let cli = Cli::parse_from([
             "git-ombl",
             "test.rs",
             "42",
@@ -276,8 +432,8 @@ mod tests {
             "desc",
         ]);
 
-        assert_eq!(cli.file, "test.rs");
-        assert_eq!(cli.line, 42);
+        assert_eq!(cli.file.as_deref(), Some("test.rs"));
+        assert_eq!(cli.line, Some(42));
         assert_eq!(cli.since, Some("2023-01-01".to_string()));
         assert_eq!(cli.ignore_revs.len(), 1);
         assert_eq!(cli.ignore_revs[0], "abc123def");
@@ -286,11 +442,225 @@ mod tests {
 
     #[test]
     fn test_cli_parsing_without_date_filters() {
-        let cli = Cli::parse_from(&["git-ombl", "test.rs", "42"]);
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42"]);
 
-        assert_eq!(cli.file, "test.rs");
-        assert_eq!(cli.line, 42);
+        assert_eq!(cli.file.as_deref(), Some("test.rs"));
+        assert_eq!(cli.line, Some(42));
         assert_eq!(cli.since, None);
         assert_eq!(cli.until, None);
     }
+
+    #[test]
+    fn test_cli_parsing_with_follow_deleted() {
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--follow-deleted"]);
+        assert!(cli.follow_deleted);
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42"]);
+        assert!(!cli.follow_deleted);
+    }
+
+    #[test]
+    fn test_cli_parsing_with_find_copies() {
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "-C"]);
+        assert!(cli.find_copies);
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--find-copies"]);
+        assert!(cli.find_copies);
+    }
+
+    #[test]
+    fn test_cli_parsing_with_limit() {
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--limit", "5"]);
+        assert_eq!(cli.limit, Some(5));
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "-l", "3"]);
+        assert_eq!(cli.limit, Some(3));
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42"]);
+        assert_eq!(cli.limit, None);
+    }
+
+    #[test]
+    fn test_cli_parsing_with_rev() {
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--rev", "v1.0..main"]);
+        assert_eq!(cli.rev, Some("v1.0..main".to_string()));
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42"]);
+        assert_eq!(cli.rev, None);
+    }
+
+    #[test]
+    fn test_cli_parsing_with_ignore_revs_files() {
+        let cli = Cli::parse_from([
+            "git-ombl",
+            "test.rs",
+            "42",
+            "--ignore-revs-file",
+            ".git-blame-ignore-revs",
+            "--no-ignore-revs-file",
+        ]);
+
+        assert_eq!(cli.ignore_revs_files, vec![".git-blame-ignore-revs"]);
+        assert!(cli.no_ignore_revs_file);
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42"]);
+        assert!(cli.ignore_revs_files.is_empty());
+        assert!(!cli.no_ignore_revs_file);
+    }
+
+    #[test]
+    fn test_cli_parsing_with_whitespace_options() {
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "-w", "--ignore-blank-lines"]);
+        assert!(cli.ignore_whitespace);
+        assert!(!cli.ignore_space_change);
+        assert!(cli.ignore_blank_lines);
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--ignore-space-change"]);
+        assert!(!cli.ignore_whitespace);
+        assert!(cli.ignore_space_change);
+        assert!(!cli.ignore_blank_lines);
+    }
+
+    #[test]
+    fn test_cli_parsing_with_range() {
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "-L", "10,+5"]);
+        assert_eq!(cli.line, None);
+        assert_eq!(cli.range, Some("10,+5".parse().unwrap()));
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "--range", ":main"]);
+        assert_eq!(cli.range, Some(LineSelector::Function("main".to_string())));
+
+        assert!(Cli::try_parse_from(["git-ombl", "test.rs", "42", "-L", "1,2"]).is_err());
+        assert!(Cli::try_parse_from(["git-ombl", "test.rs", "-L", "x,y"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parsing_whole_file() {
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "--format", "table"]);
+
+        assert_eq!(cli.file.as_deref(), Some("test.rs"));
+        assert_eq!(cli.line, None);
+        assert_eq!(cli.range, None);
+        assert!(matches!(cli.format, Format::Table));
+    }
+
+    #[test]
+    fn test_cli_parsing_cache_commands() {
+        let cli = Cli::parse_from(["git-ombl", "cache", "stats"]);
+        assert_eq!(cli.file, None);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Cache {
+                action: CacheAction::Stats
+            })
+        ));
+
+        let cli = Cli::parse_from(["git-ombl", "cache", "clear"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Cache {
+                action: CacheAction::Clear
+            })
+        ));
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--no-cache"]);
+        assert!(cli.command.is_none());
+        assert!(cli.no_cache);
+
+        assert!(Cli::try_parse_from(["git-ombl"]).is_err());
+        assert!(Cli::try_parse_from(["git-ombl", "cache"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parsing_merge_options() {
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42"]);
+        assert!(!cli.first_parent);
+        assert!(!cli.no_merges);
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--first-parent", "--no-merges"]);
+        assert!(cli.first_parent);
+        assert!(cli.no_merges);
+    }
+
+    #[test]
+    fn test_cli_parsing_order() {
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42"]);
+        assert_eq!(cli.order, CommitOrder::Topo);
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--order", "author-date"]);
+        assert_eq!(cli.order, CommitOrder::AuthorDate);
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--order", "committer-date"]);
+        assert_eq!(cli.order, CommitOrder::CommitterDate);
+
+        assert!(Cli::try_parse_from(["git-ombl", "test.rs", "42", "--order", "random"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parsing_no_mailmap() {
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42"]);
+        assert!(!cli.no_mailmap);
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--no-mailmap"]);
+        assert!(cli.no_mailmap);
+    }
+
+    #[test]
+    fn test_cli_parsing_full_message() {
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42"]);
+        assert!(!cli.full_message);
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--full-message"]);
+        assert!(cli.full_message);
+    }
+
+    #[test]
+    fn test_cli_parsing_patch() {
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42"]);
+        assert!(!cli.patch);
+        assert_eq!(cli.unified, None);
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "-p"]);
+        assert!(cli.patch);
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "-U1"]);
+        assert!(!cli.patch);
+        assert_eq!(cli.unified, Some(1));
+    }
+
+    #[test]
+    fn test_cli_parsing_date() {
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42"]);
+        assert_eq!(cli.date, None);
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--date", "relative"]);
+        assert_eq!(cli.date, Some(DateFormat::Relative));
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42", "--date=format:%d.%m.%Y"]);
+        assert_eq!(cli.date, Some(DateFormat::Format("%d.%m.%Y".to_string())));
+
+        assert!(Cli::try_parse_from(["git-ombl", "test.rs", "42", "--date", "soon"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parsing_with_relative_dates_and_timezone() {
+        let cli = Cli::parse_from([
+            "git-ombl",
+            "test.rs",
+            "42",
+            "--since",
+            "2 weeks ago",
+            "--until",
+            "2023-06",
+            "--timezone",
+            "+0200",
+        ]);
+
+        assert_eq!(cli.since, Some("2 weeks ago".to_string()));
+        assert_eq!(cli.until, Some("2023-06".to_string()));
+        assert_eq!(cli.timezone, Some("+0200".to_string()));
+
+        let cli = Cli::parse_from(["git-ombl", "test.rs", "42"]);
+        assert_eq!(cli.timezone, None);
+    }
 }
@@ -1,6 +1,6 @@
 use git_ombl::{
-    ColoredFormatter, GitAdapter, JsonFormatter, LineHistory, LineHistoryUseCase, OutputFormatter,
-    SortOrder, TableFormatter, YamlFormatter,
+    ColoredFormatter, GitAdapter, HistoryOptions, JsonFormatter, LineHistory, LineHistoryUseCase,
+    OutputFormatter, SortOrder, TableFormatter, YamlFormatter,
 };
 use std::env;
 
@@ -38,7 +38,7 @@ fn assert_complete_history_traversal(history: &LineHistory) {
 fn test_sample_file_line_history_integration() {
     let use_case = create_use_case();
     let history = use_case
-        .get_line_history("test_sample.rs", 1, SortOrder::Asc, &[], None, None)
+        .get_line_history("test_sample.rs", 1, &HistoryOptions::default())
         .unwrap();
 
     assert_basic_history_properties(&history, "test_sample.rs", 1);
@@ -48,7 +48,7 @@ fn test_sample_file_line_history_integration() {
 fn test_sample_file_complete_history_traversal() {
     let use_case = create_use_case();
     let history = use_case
-        .get_line_history("test_sample.rs", 1, SortOrder::Asc, &[], None, None)
+        .get_line_history("test_sample.rs", 1, &HistoryOptions::default())
         .unwrap();
 
     assert_basic_history_properties(&history, "test_sample.rs", 1);
@@ -61,13 +61,13 @@ fn test_sample_file_different_lines() {
 
     // Test line 1 (modified 3 times)
     let history_line1 = use_case
-        .get_line_history("test_sample.rs", 1, SortOrder::Asc, &[], None, None)
+        .get_line_history("test_sample.rs", 1, &HistoryOptions::default())
         .unwrap();
     assert_basic_history_properties(&history_line1, "test_sample.rs", 1);
 
     // Test line 2 (should have only 1 commit - initial)
     let history_line2 = use_case
-        .get_line_history("test_sample.rs", 2, SortOrder::Asc, &[], None, None)
+        .get_line_history("test_sample.rs", 2, &HistoryOptions::default())
         .unwrap();
     assert_basic_history_properties(&history_line2, "test_sample.rs", 2);
 
@@ -82,7 +82,7 @@ fn test_sample_file_different_lines() {
 fn test_sample_file_with_all_formatters() {
     let use_case = create_use_case();
     let history = use_case
-        .get_line_history("test_sample.rs", 1, SortOrder::Asc, &[], None, None)
+        .get_line_history("test_sample.rs", 1, &HistoryOptions::default())
         .unwrap();
 
     assert_basic_history_properties(&history, "test_sample.rs", 1);
@@ -126,7 +126,7 @@ fn test_sample_file_with_all_formatters() {
 fn test_sample_file_commit_messages_and_authors() {
     let use_case = create_use_case();
     let history = use_case
-        .get_line_history("test_sample.rs", 1, SortOrder::Asc, &[], None, None)
+        .get_line_history("test_sample.rs", 1, &HistoryOptions::default())
         .unwrap();
 
     assert_basic_history_properties(&history, "test_sample.rs", 1);
@@ -135,7 +135,7 @@ fn test_sample_file_commit_messages_and_authors() {
     let commit_messages: Vec<&str> = history
         .entries
         .iter()
-        .map(|entry| entry.message.as_str())
+        .map(|entry| entry.subject.as_str())
         .collect();
 
     // Should contain our test commit messages
@@ -156,7 +156,7 @@ fn test_sample_file_commit_messages_and_authors() {
             "All entries should have a commit hash"
         );
         assert!(
-            !entry.message.is_empty(),
+            !entry.subject.is_empty(),
             "All entries should have a commit message"
         );
     }
@@ -166,7 +166,7 @@ fn test_sample_file_commit_messages_and_authors() {
 fn test_sample_file_change_types() {
     let use_case = create_use_case();
     let history = use_case
-        .get_line_history("test_sample.rs", 1, SortOrder::Asc, &[], None, None)
+        .get_line_history("test_sample.rs", 1, &HistoryOptions::default())
         .unwrap();
 
     assert_basic_history_properties(&history, "test_sample.rs", 1);
@@ -191,12 +191,19 @@ fn test_sample_file_sort_order_integration() {
 
     // Test ascending order (oldest first)
     let history_asc = use_case
-        .get_line_history("test_sample.rs", 1, SortOrder::Asc, &[], None, None)
+        .get_line_history("test_sample.rs", 1, &HistoryOptions::default())
         .unwrap();
 
     // Test descending order (newest first)
     let history_desc = use_case
-        .get_line_history("test_sample.rs", 1, SortOrder::Desc, &[], None, None)
+        .get_line_history(
+            "test_sample.rs",
+            1,
+            &HistoryOptions {
+                sort_order: SortOrder::Desc,
+                ..Default::default()
+            },
+        )
         .unwrap();
 
     assert_basic_history_properties(&history_asc, "test_sample.rs", 1);
@@ -244,7 +251,7 @@ fn test_sample_file_ignore_revisions_integration() {
 
     // First get all commits to find one to ignore
     let history_all = use_case
-        .get_line_history("test_sample.rs", 1, SortOrder::Asc, &[], None, None)
+        .get_line_history("test_sample.rs", 1, &HistoryOptions::default())
         .unwrap();
 
     assert_basic_history_properties(&history_all, "test_sample.rs", 1);
@@ -263,10 +270,10 @@ fn test_sample_file_ignore_revisions_integration() {
         .get_line_history(
             "test_sample.rs",
             1,
-            SortOrder::Asc,
-            &ignore_revs,
-            None,
-            None,
+            &HistoryOptions {
+                ignore_revs: ignore_revs.clone(),
+                ..Default::default()
+            },
         )
         .unwrap();
 
@@ -295,10 +302,10 @@ fn test_sample_file_ignore_revisions_integration() {
             .get_line_history(
                 "test_sample.rs",
                 1,
-                SortOrder::Asc,
-                &ignore_revs_multiple,
-                None,
-                None,
+                &HistoryOptions {
+                    ignore_revs: ignore_revs_multiple.clone(),
+                    ..Default::default()
+                },
             )
             .unwrap();
 
@@ -322,20 +329,18 @@ fn test_sample_file_ignore_revisions_integration() {
         }
     }
 
-    // Test with non-existent hash - should return same results as no ignore
+    // Test with non-existent hash - should be rejected
     let fake_ignore_revs = vec!["fakehash123".to_string()];
-    let history_fake_ignore = use_case
-        .get_line_history(
-            "test_sample.rs",
-            1,
-            SortOrder::Asc,
-            &fake_ignore_revs,
-            None,
-            None,
-        )
-        .unwrap();
+    let history_fake_ignore = use_case.get_line_history(
+        "test_sample.rs",
+        1,
+        &HistoryOptions {
+            ignore_revs: fake_ignore_revs.clone(),
+            ..Default::default()
+        },
+    );
 
-    assert_eq!(history_fake_ignore.entries.len(), history_all.entries.len());
+    assert!(history_fake_ignore.is_err());
 }
 
 #[test]
@@ -344,7 +349,7 @@ fn test_sample_file_date_filtering_integration() {
 
     // First get all commits to understand timestamps
     let history_all = use_case
-        .get_line_history("test_sample.rs", 1, SortOrder::Asc, &[], None, None)
+        .get_line_history("test_sample.rs", 1, &HistoryOptions::default())
         .unwrap();
 
     assert_basic_history_properties(&history_all, "test_sample.rs", 1);
@@ -364,10 +369,10 @@ fn test_sample_file_date_filtering_integration() {
         .get_line_history(
             "test_sample.rs",
             1,
-            SortOrder::Asc,
-            &[],
-            Some(&since_date),
-            None,
+            &HistoryOptions {
+                since: Some(since_date.to_string()),
+                ..Default::default()
+            },
         )
         .unwrap();
 
@@ -388,10 +393,10 @@ fn test_sample_file_date_filtering_integration() {
             .get_line_history(
                 "test_sample.rs",
                 1,
-                SortOrder::Asc,
-                &[],
-                None,
-                Some(&until_date),
+                &HistoryOptions {
+                    until: Some(until_date.to_string()),
+                    ..Default::default()
+                },
             )
             .unwrap();
 
@@ -417,10 +422,11 @@ fn test_sample_file_date_filtering_integration() {
             .get_line_history(
                 "test_sample.rs",
                 1,
-                SortOrder::Asc,
-                &[],
-                Some(&since_date),
-                Some(&until_date),
+                &HistoryOptions {
+                    since: Some(since_date.to_string()),
+                    until: Some(until_date.to_string()),
+                    ..Default::default()
+                },
             )
             .unwrap();
 
@@ -442,30 +448,30 @@ fn test_sample_file_date_format_compatibility() {
     let result_iso = use_case.get_line_history(
         "test_sample.rs",
         1,
-        SortOrder::Asc,
-        &[],
-        Some(iso_date),
-        None,
+        &HistoryOptions {
+            since: Some(iso_date.to_string()),
+            ..Default::default()
+        },
     );
     assert!(result_iso.is_ok());
 
     let result_simple = use_case.get_line_history(
         "test_sample.rs",
         1,
-        SortOrder::Asc,
-        &[],
-        Some(simple_date),
-        None,
+        &HistoryOptions {
+            since: Some(simple_date.to_string()),
+            ..Default::default()
+        },
     );
     assert!(result_simple.is_ok());
 
     let result_datetime = use_case.get_line_history(
         "test_sample.rs",
         1,
-        SortOrder::Asc,
-        &[],
-        Some(datetime_format),
-        None,
+        &HistoryOptions {
+            since: Some(datetime_format.to_string()),
+            ..Default::default()
+        },
     );
     assert!(result_datetime.is_ok());
 }
@@ -476,7 +482,7 @@ fn test_sample_file_date_filtering_with_other_options() {
 
     // Get all commits first
     let history_all = use_case
-        .get_line_history("test_sample.rs", 1, SortOrder::Asc, &[], None, None)
+        .get_line_history("test_sample.rs", 1, &HistoryOptions::default())
         .unwrap();
 
     if history_all.entries.len() >= 2 {
@@ -489,10 +495,12 @@ fn test_sample_file_date_filtering_with_other_options() {
             .get_line_history(
                 "test_sample.rs",
                 1,
-                SortOrder::Desc,
-                &ignore_revs,
-                Some(since_date),
-                None,
+                &HistoryOptions {
+                    sort_order: SortOrder::Desc,
+                    ignore_revs: ignore_revs.clone(),
+                    since: Some(since_date.to_string()),
+                    ..Default::default()
+                },
             )
             .unwrap();
 
@@ -515,3 +523,69 @@ fn test_sample_file_date_filtering_with_other_options() {
         }
     }
 }
+
+/// Commits `content` to test.txt at the given time, in UTC.
+fn commit_at(repo: &git2::Repository, content: &str, message: &str, time: i64) {
+    std::fs::write(repo.workdir().unwrap().join("test.txt"), content).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("test.txt")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature =
+        git2::Signature::new("Test User", "test@example.com", &git2::Time::new(time, 0)).unwrap();
+    let parents: Vec<git2::Commit> = repo
+        .head()
+        .ok()
+        .map(|head| head.peel_to_commit().unwrap())
+        .into_iter()
+        .collect();
+    let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &parent_refs,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_bare_dates_use_the_local_offset_of_that_date() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let repo = git2::Repository::init(temp_dir.path()).unwrap();
+    // 11:30 in Paris, in winter and in summer
+    commit_at(&repo, "winter\n", "Winter", 1673778600); // 2023-01-15T10:30:00Z
+    commit_at(&repo, "summer\n", "Summer", 1689413400); // 2023-07-15T09:30:00Z
+
+    // Whichever period today falls in, one of the dates is in the other one
+    let subjects = |since: &str| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_git-ombl"))
+            .args([
+                "test.txt",
+                "1",
+                "--format",
+                "json",
+                "--no-cache",
+                "--since",
+                since,
+            ])
+            .current_dir(temp_dir.path())
+            .env("TZ", "Europe/Paris")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        let history: LineHistory = serde_json::from_slice(&output.stdout).unwrap();
+        history
+            .entries
+            .into_iter()
+            .map(|entry| entry.subject)
+            .collect::<Vec<_>>()
+    };
+
+    // 12:00 CET is after the winter commit
+    assert_eq!(subjects("2023-01-15 12:00"), vec!["Summer"]);
+    // 11:00 CEST is before the summer commit
+    assert_eq!(subjects("2023-07-15 11:00"), vec!["Summer"]);
+}